@@ -0,0 +1,252 @@
+// Generates typed inputs and outputs of the CryptoScrypto functions
+// from the package definition (*.rpd) file.
+//
+// Each function gets:
+// - an input struct with the fields of its "<Blueprint>_<function>_Input" schema type
+// - an implementation of `CryptoScryptoFunctionInput` with its output type, decoding
+//   of the input from the call arguments and its human-readable description
+// So when the blueprint changes, the code using the old signatures stops compiling.
+use sbor::prelude::*;
+use scrypto::blueprints::package::{PackageDefinition, TypeRef};
+use std::{env, fs, path::Path};
+use transaction::prelude::*;
+
+const CRYPTO_SCRYPTO_RPD_PATH: &str = "crypto_scrypto/crypto_scrypto.rpd";
+
+// Types, which are mapped by their schema name rather than by their structure
+const NAMED_TYPES: [&str; 6] = [
+    "Hash",
+    "Bls12381G1PublicKey",
+    "Bls12381G2Signature",
+    "PackageAddress",
+    "ComponentAddress",
+    "Decimal",
+];
+
+fn main() {
+    println!("cargo:rerun-if-changed={}", CRYPTO_SCRYPTO_RPD_PATH);
+
+    let rpd: PackageDefinition =
+        manifest_decode(&fs::read(CRYPTO_SCRYPTO_RPD_PATH).unwrap()).unwrap();
+
+    let mut code = String::from("// Generated by build.rs, do not edit.\n");
+    let prefix_with_blueprint = rpd.blueprints.len() > 1;
+
+    for (blueprint_name, blueprint) in &rpd.blueprints {
+        let schema = &blueprint.schema.schema;
+
+        for (function_name, function) in &blueprint.schema.functions.functions {
+            // Only functions are supported, methods need a component to call
+            if function.receiver.is_some() {
+                continue;
+            }
+            let struct_name = if prefix_with_blueprint {
+                format!("{}{}Input", blueprint_name, to_camel_case(function_name))
+            } else {
+                format!("{}Input", to_camel_case(function_name))
+            };
+            let input_type = static_type(&function.input);
+            let output_type = rust_type(schema, static_type(&function.output));
+
+            let field_types = match schema.resolve_type_kind(input_type) {
+                Some(TypeKind::Tuple { field_types }) => field_types.clone(),
+                kind => panic!("Unexpected input of {}: {:?}", function_name, kind),
+            };
+            let metadata = schema.resolve_type_metadata(input_type).unwrap();
+            let field_names: Vec<String> = match &metadata.child_names {
+                Some(ChildNames::NamedFields(names)) => {
+                    names.iter().map(|n| n.to_string()).collect()
+                }
+                _ => (0..field_types.len())
+                    .map(|i| format!("field_{}", i))
+                    .collect(),
+            };
+
+            code += &format!(
+                "\n/// Input of the `{}::{}` function (`{}`)\n",
+                blueprint_name,
+                function_name,
+                metadata.get_name().unwrap_or_default()
+            );
+            code += "#[derive(Clone, Debug)]\n";
+            code += &format!("pub struct {} {{\n", struct_name);
+            for (name, type_index) in field_names.iter().zip(&field_types) {
+                code += &format!("    pub {}: {},\n", name, rust_type(schema, *type_index));
+            }
+            code += "}\n\n";
+
+            let args: Vec<String> = field_names
+                .iter()
+                .map(|name| format!("&self.{}", name))
+                .collect();
+            let fields_tuple_type: String = field_types
+                .iter()
+                .map(|type_index| format!("{}, ", rust_type(schema, *type_index)))
+                .collect();
+            let fields_tuple: String = field_names
+                .iter()
+                .map(|name| format!("{}, ", name))
+                .collect();
+            let field_descriptions: Vec<String> = field_names
+                .iter()
+                .zip(&field_types)
+                .map(|(name, type_index)| {
+                    let value = format!("self.{}", name);
+                    let desc = describe_expr(schema, *type_index, &value, 0);
+                    format!("format!(\"{}: {{}}\", {})", name, desc)
+                })
+                .collect();
+            code += &format!(
+                "impl CryptoScryptoFunctionInput for {} {{\n\
+                 \x20   const BLUEPRINT_NAME: &'static str = {:?};\n\
+                 \x20   const FUNCTION_NAME: &'static str = {:?};\n\
+                 \x20   type Output = {};\n\n\
+                 \x20   fn add_to_manifest(\n\
+                 \x20       &self,\n\
+                 \x20       builder: ManifestBuilder,\n\
+                 \x20       package_address: PackageAddress,\n\
+                 \x20   ) -> ManifestBuilder {{\n\
+                 \x20       builder.call_function(\n\
+                 \x20           package_address,\n\
+                 \x20           Self::BLUEPRINT_NAME,\n\
+                 \x20           Self::FUNCTION_NAME,\n\
+                 \x20           manifest_args!({}),\n\
+                 \x20       )\n\
+                 \x20   }}\n\n\
+                 \x20   fn from_args(args: &ManifestValue) -> Option<Self> {{\n\
+                 \x20       let bytes = manifest_encode(args).ok()?;\n\
+                 \x20       let ({}): ({}) = manifest_decode(&bytes).ok()?;\n\
+                 \x20       Some(Self {{ {} }})\n\
+                 \x20   }}\n\n\
+                 \x20   fn describe(&self) -> String {{\n\
+                 \x20       [{}].join(\", \")\n\
+                 \x20   }}\n\
+                 }}\n",
+                struct_name,
+                blueprint_name,
+                function_name,
+                output_type,
+                args.join(", "),
+                fields_tuple,
+                fields_tuple_type,
+                field_names.join(", "),
+                field_descriptions.join(", ")
+            );
+        }
+    }
+
+    let out_path = Path::new(&env::var("OUT_DIR").unwrap()).join("crypto_scrypto.rs");
+    fs::write(out_path, code).unwrap();
+}
+
+fn static_type(type_ref: &TypeRef<LocalTypeIndex>) -> LocalTypeIndex {
+    match type_ref {
+        TypeRef::Static(type_index) => *type_index,
+        TypeRef::Generic(_) => panic!("Generic types are not supported"),
+    }
+}
+
+fn rust_type(schema: &ScryptoSchema, type_index: LocalTypeIndex) -> String {
+    if let Some(name) = schema
+        .resolve_type_metadata(type_index)
+        .and_then(|metadata| metadata.get_name())
+    {
+        if NAMED_TYPES.contains(&name) {
+            return name.to_string();
+        }
+    }
+
+    match schema.resolve_type_kind(type_index) {
+        Some(TypeKind::Bool) => "bool".to_string(),
+        Some(TypeKind::I8) => "i8".to_string(),
+        Some(TypeKind::I16) => "i16".to_string(),
+        Some(TypeKind::I32) => "i32".to_string(),
+        Some(TypeKind::I64) => "i64".to_string(),
+        Some(TypeKind::I128) => "i128".to_string(),
+        Some(TypeKind::U8) => "u8".to_string(),
+        Some(TypeKind::U16) => "u16".to_string(),
+        Some(TypeKind::U32) => "u32".to_string(),
+        Some(TypeKind::U64) => "u64".to_string(),
+        Some(TypeKind::U128) => "u128".to_string(),
+        Some(TypeKind::String) => "String".to_string(),
+        Some(TypeKind::Array { element_type }) => {
+            format!("Vec<{}>", rust_type(schema, *element_type))
+        }
+        Some(TypeKind::Tuple { field_types }) if field_types.is_empty() => "()".to_string(),
+        Some(TypeKind::Tuple { field_types }) => {
+            let fields: Vec<String> = field_types
+                .iter()
+                .map(|field_type| rust_type(schema, *field_type))
+                .collect();
+            if fields.len() == 1 {
+                format!("({},)", fields[0])
+            } else {
+                format!("({})", fields.join(", "))
+            }
+        }
+        kind => panic!("Unsupported type {:?}: {:?}", type_index, kind),
+    }
+}
+
+// Expression formatting the value of the given type into a human-readable String.
+// Depth makes the argument names of the nested closures unique.
+fn describe_expr(
+    schema: &ScryptoSchema,
+    type_index: LocalTypeIndex,
+    value: &str,
+    depth: usize,
+) -> String {
+    if let Some(name) = schema
+        .resolve_type_metadata(type_index)
+        .and_then(|metadata| metadata.get_name())
+    {
+        if ["Hash", "Bls12381G1PublicKey", "Bls12381G2Signature"].contains(&name) {
+            return format!("{}.to_string()", value);
+        }
+        if NAMED_TYPES.contains(&name) {
+            return format!("format!(\"{{:?}}\", {})", value);
+        }
+    }
+
+    match schema.resolve_type_kind(type_index) {
+        Some(TypeKind::Array { element_type })
+            if matches!(schema.resolve_type_kind(*element_type), Some(TypeKind::U8)) =>
+        {
+            format!("format_bytes(&{})", value)
+        }
+        Some(TypeKind::Array { element_type }) => {
+            let element = format!("e{}", depth);
+            format!(
+                "format!(\"[{{}}]\", {}.iter().map(|{}| {}).collect::<Vec<String>>().join(\", \"))",
+                value,
+                element,
+                describe_expr(schema, *element_type, &element, depth + 1)
+            )
+        }
+        Some(TypeKind::Tuple { field_types }) => {
+            let fields: Vec<String> = field_types
+                .iter()
+                .enumerate()
+                .map(|(i, field_type)| {
+                    describe_expr(schema, *field_type, &format!("{}.{}", value, i), depth)
+                })
+                .collect();
+            format!("format!(\"({{}})\", [{}].join(\", \"))", fields.join(", "))
+        }
+        Some(TypeKind::String) => format!("format!(\"{{:?}}\", {})", value),
+        _ => format!("{}.to_string()", value),
+    }
+}
+
+// Eg. "bls12381_v1_verify" -> "Bls12381V1Verify"
+fn to_camel_case(name: &str) -> String {
+    name.split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
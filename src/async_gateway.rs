@@ -0,0 +1,323 @@
+use crate::gateway::*;
+use reqwest::{header::*, Client};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use transaction::prelude::*;
+
+impl GatewayClientConfig {
+    pub fn build_client(&self) -> Result<Client, String> {
+        self.configure(Client::builder())?
+            .build()
+            .map_err(|err| err.to_string())
+    }
+}
+
+/// Non-blocking variant of `GatewayApiClient` for use within async runtimes (eg. Tokio).
+/// It has the same API, including failover between gateways, but its request methods
+/// are async and `current_epoch` returns the error instead of panicking.
+#[derive(Clone, Debug)]
+pub struct AsyncGatewayApiClient {
+    urls: Vec<String>,
+    // Index of the gateway the requests are routed to, shared between clones
+    active: Arc<AtomicUsize>,
+    client: Client,
+    retry_config: RetryConfig,
+    max_ledger_age: Duration,
+    event_hook: Option<EventHook>,
+}
+
+impl AsyncGatewayApiClient {
+    pub fn new(url: &str) -> Self {
+        Self::with_urls(&[url])
+    }
+
+    /// Client failing over between given gateways.
+    /// Requests are routed to the first one until it fails.
+    pub fn with_urls<S: AsRef<str>>(urls: &[S]) -> Self {
+        assert!(!urls.is_empty(), "At least one gateway URL required");
+        Self {
+            urls: urls.iter().map(|url| url.as_ref().to_string()).collect(),
+            active: Arc::new(AtomicUsize::new(0)),
+            client: Client::new(),
+            retry_config: RetryConfig::default(),
+            max_ledger_age: DEFAULT_MAX_LEDGER_AGE,
+            event_hook: None,
+        }
+    }
+
+    pub fn with_client_config(mut self, config: &GatewayClientConfig) -> Self {
+        self.client = config
+            .build_client()
+            .unwrap_or_else(|err| panic!("Invalid gateway client configuration: {}", err));
+        self
+    }
+
+    pub fn with_retry_config(mut self, retry_config: RetryConfig) -> Self {
+        self.retry_config = retry_config;
+        self
+    }
+
+    pub fn with_max_ledger_age(mut self, max_ledger_age: Duration) -> Self {
+        self.max_ledger_age = max_ledger_age;
+        self
+    }
+
+    /// Call the hook on failover events, eg. to log them
+    pub fn with_event_hook<F: Fn(&GatewayEvent) + Send + Sync + 'static>(
+        mut self,
+        hook: F,
+    ) -> Self {
+        self.event_hook = Some(EventHook::new(hook));
+        self
+    }
+
+    fn report(&self, event: GatewayEvent) {
+        EventHook::report(&self.event_hook, event);
+    }
+
+    pub fn urls(&self) -> &[String] {
+        &self.urls
+    }
+
+    /// URL of the gateway the requests are currently routed to
+    pub fn url(&self) -> &str {
+        &self.urls[self.active.load(Ordering::SeqCst)]
+    }
+
+    pub fn max_ledger_age(&self) -> Duration {
+        self.max_ledger_age
+    }
+
+    /// Probe the gateway with the status request (no retries).
+    /// Gateway is healthy if it responds and its ledger is not stale.
+    pub async fn check_health(&self, url: &str) -> Result<GatewayStatus, String> {
+        let resp = self
+            .client
+            .post(url.to_string() + "/status/gateway-status")
+            .header(ACCEPT, "application/json")
+            .header(CONTENT_TYPE, "application/json")
+            .send()
+            .await
+            .map_err(|err| format!("request error: {}", err))?;
+        if !resp.status().is_success() {
+            return Err(format!("response status {}", resp.status()));
+        }
+        let status: GatewayStatus = resp
+            .json()
+            .await
+            .map_err(|err| format!("invalid response: {}", err))?;
+
+        check_ledger_age(&status, self.max_ledger_age)?;
+        Ok(status)
+    }
+
+    /// Route requests to the first healthy gateway, starting from the currently active one.
+    /// Returns false if none of the gateways is healthy.
+    pub async fn select_healthy_gateway(&self) -> bool {
+        self.select_healthy_gateway_except(&HashSet::new()).await
+    }
+
+    // Like select_healthy_gateway, but gateways with the given indices are skipped
+    async fn select_healthy_gateway_except(&self, excluded: &HashSet<usize>) -> bool {
+        let current = self.active.load(Ordering::SeqCst);
+        for offset in 0..self.urls.len() {
+            let idx = (current + offset) % self.urls.len();
+            if excluded.contains(&idx) {
+                continue;
+            }
+            match self.check_health(&self.urls[idx]).await {
+                Ok(_) => {
+                    self.report(GatewayEvent::Selected {
+                        url: self.urls[idx].clone(),
+                    });
+                    self.active.store(idx, Ordering::SeqCst);
+                    return true;
+                }
+                Err(error) => self.report(GatewayEvent::Unhealthy {
+                    url: self.urls[idx].clone(),
+                    error,
+                }),
+            }
+        }
+        false
+    }
+
+    // Send the request to the active gateway. If it keeps failing after all retries,
+    // then switch to another healthy gateway and try again. Each gateway is tried
+    // at most once per request, the error is returned when all of them failed.
+    async fn post<B: Serialize + ?Sized>(
+        &self,
+        path: &str,
+        body: Option<&B>,
+    ) -> Result<(String, u32), TransactionError> {
+        let mut total_retries = 0;
+        let mut tried = HashSet::new();
+        loop {
+            let idx = self.active.load(Ordering::SeqCst);
+            let url = self.urls[idx].clone();
+            match self.post_to(&url, path, body).await {
+                Ok((resp, retries)) => return Ok((resp, total_retries + retries)),
+                Err((error, retries)) => {
+                    total_retries += retries + 1;
+                    tried.insert(idx);
+                    self.report(GatewayEvent::RequestFailed {
+                        url,
+                        error: error.clone(),
+                    });
+                    if tried.len() == self.urls.len()
+                        || !self.select_healthy_gateway_except(&tried).await
+                    {
+                        return Err(TransactionError::request_failed(error));
+                    }
+                }
+            }
+        }
+    }
+
+    // Send the request and retry it on transport errors, 5xx and 429 responses.
+    // Returns the response body along with the number of retries made,
+    // or the last error if the gateway still fails after all retries.
+    async fn post_to<B: Serialize + ?Sized>(
+        &self,
+        url: &str,
+        path: &str,
+        body: Option<&B>,
+    ) -> Result<(String, u32), (String, u32)> {
+        let mut retry = 0;
+        loop {
+            let mut request = self
+                .client
+                .post(url.to_string() + path)
+                .header(ACCEPT, "application/json")
+                .header(CONTENT_TYPE, "application/json");
+            if let Some(body) = body {
+                request = request.json(body);
+            }
+
+            let retry_after = match request.send().await {
+                Ok(resp) if !is_retryable_status(resp.status()) => {
+                    return resp
+                        .text()
+                        .await
+                        .map(|text| (text, retry))
+                        .map_err(|err| (format!("response body error: {:?}", err), retry))
+                }
+                Ok(resp) if retry >= self.retry_config.max_retries => {
+                    return Err((format!("response status {}", resp.status()), retry))
+                }
+                Ok(resp) => parse_retry_after(resp.headers()),
+                Err(err) if retry >= self.retry_config.max_retries => {
+                    return Err((format!("request error: {:?}", err), retry))
+                }
+                Err(_) => None,
+            };
+
+            tokio::time::sleep(self.retry_config.backoff(retry, retry_after)).await;
+            retry += 1;
+        }
+    }
+
+    pub async fn gateway_status(&self) -> Result<GatewayStatus, TransactionError> {
+        let (resp, _) = self.post::<()>("/status/gateway-status", None).await?;
+
+        parse_response(&resp)
+    }
+
+    pub async fn network_configuration(&self) -> Result<NetworkConfiguration, TransactionError> {
+        let (resp, _) = self
+            .post::<()>("/status/network-configuration", None)
+            .await?;
+
+        parse_response(&resp)
+    }
+
+    pub async fn current_epoch(&self) -> Result<u64, TransactionError> {
+        Ok(self.gateway_status().await?.ledger_state.epoch)
+    }
+
+    // Submission is retried as well, since the same payload always has the same intent hash.
+    pub async fn transaction_submit(
+        &self,
+        transaction: NotarizedTransactionV1,
+    ) -> Result<TransactionSubmit, TransactionError> {
+        let notarized_transaction_bytes = transaction.to_payload_bytes().unwrap();
+        let notarized_transaction_hex = hex::encode(&notarized_transaction_bytes);
+
+        let mut map = HashMap::new();
+        map.insert("notarized_transaction_hex", notarized_transaction_hex);
+
+        let (resp, retries) = self.post("/transaction/submit", Some(&map)).await?;
+
+        parse_response::<TransactionSubmit>(&resp).map(|submit| TransactionSubmit {
+            // If the payload is reported as duplicate after a retry, then it was
+            // submitted by one of the previous attempts, whose response was lost.
+            duplicate: submit.duplicate && retries == 0,
+        })
+    }
+
+    pub async fn transaction_status(
+        &self,
+        intent_hash: &str,
+    ) -> Result<TransactionStatus, TransactionError> {
+        let mut map = HashMap::new();
+        map.insert("intent_hash", intent_hash);
+
+        let (resp, _) = self.post("/transaction/status", Some(&map)).await?;
+
+        parse_response(&resp)
+    }
+
+    pub async fn transaction_details(
+        &self,
+        intent_hash: &str,
+    ) -> Result<TransactionDetails, TransactionError> {
+        self.transaction_details_with_opt_ins(intent_hash, TransactionDetailsOptIns::default())
+            .await
+    }
+
+    pub async fn transaction_details_with_opt_ins(
+        &self,
+        intent_hash: &str,
+        opt_ins: TransactionDetailsOptIns,
+    ) -> Result<TransactionDetails, TransactionError> {
+        let request = TransactionDetailsRequest {
+            intent_hash: intent_hash.to_string(),
+            opt_ins,
+        };
+
+        let (resp, _) = self
+            .post("/transaction/committed-details", Some(&request))
+            .await?;
+
+        parse_response(&resp)
+    }
+
+    // Poll the transaction status until it is no longer pending,
+    // then get the transaction details.
+    pub async fn wait_for_transaction(
+        &self,
+        intent_hash: &str,
+    ) -> Result<TransactionDetails, TransactionError> {
+        loop {
+            let status = self.transaction_status(intent_hash).await?;
+            if !status.status.eq("Pending") {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(1000)).await;
+        }
+
+        self.transaction_details(intent_hash).await
+    }
+
+    pub async fn stream_transactions(
+        &self,
+        request: &StreamTransactionsRequest,
+    ) -> Result<StreamTransactions, TransactionError> {
+        let (resp, _) = self.post("/stream/transactions", Some(request)).await?;
+
+        parse_response(&resp)
+    }
+}
@@ -0,0 +1,637 @@
+//! Off-ledger BLS12-381 operations, compatible with the Scrypto BLS12-381 v1 functions
+//! (public keys in G1, signatures in G2, proof of possession ciphersuite).
+
+use base64::Engine;
+use blst::min_pk::{AggregatePublicKey, AggregateSignature, PublicKey, SecretKey, Signature};
+use blst::{
+    blst_p1, blst_p1_add_or_double, blst_p1_affine, blst_p1_affine_compress, blst_p1_affine_in_g1,
+    blst_p1_affine_is_inf, blst_p1_affine_serialize, blst_p1_cneg, blst_p1_compress,
+    blst_p1_deserialize, blst_p1_from_affine, blst_p1_uncompress, blst_p2_affine,
+    blst_p2_affine_compress, blst_p2_affine_in_g2, blst_p2_affine_is_inf, blst_p2_affine_serialize,
+    blst_p2_deserialize, BLST_ERROR,
+};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::str::FromStr;
+use transaction::prelude::*;
+use unicode_normalization::UnicodeNormalization;
+
+/// Domain separation tag of message signatures, the same as used by Scrypto
+pub const BLS_SIG_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+/// Domain separation tag of proofs of possession
+pub const BLS_POP_DST: &[u8] = b"BLS_POP_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// Hash applied to the message before signing and verification.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Prehash {
+    Keccak256,
+    Sha256,
+    Blake2b,
+    None,
+}
+
+impl Prehash {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Keccak256 => "keccak256",
+            Self::Sha256 => "sha256",
+            Self::Blake2b => "blake2b",
+            Self::None => "none",
+        }
+    }
+
+    /// Digest which is actually signed and verified, the message itself if not prehashed.
+    pub fn digest(&self, msg: &[u8]) -> Vec<u8> {
+        match self {
+            Self::Keccak256 => keccak256_hash(msg).to_vec(),
+            Self::Sha256 => Sha256::digest(msg).to_vec(),
+            Self::Blake2b => blake2b_256_hash(msg).to_vec(),
+            Self::None => msg.to_vec(),
+        }
+    }
+}
+
+impl fmt::Display for Prehash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Prehash {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "keccak256" => Ok(Self::Keccak256),
+            "sha256" => Ok(Self::Sha256),
+            "blake2b" => Ok(Self::Blake2b),
+            "none" => Ok(Self::None),
+            _ => Err(format!(
+                "Unknown prehash '{}', expected one of: keccak256, sha256, blake2b, none",
+                s
+            )),
+        }
+    }
+}
+
+/// BLS12-381 secret key, whose public key is in G1.
+pub struct BlsSecretKey(SecretKey);
+
+impl BlsSecretKey {
+    /// Secret key from its 32-byte big-endian encoding.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        SecretKey::from_bytes(bytes)
+            .map(Self)
+            .map_err(|err| format!("Invalid secret key: {:?}", err))
+    }
+
+    /// Secret key of the small integer, for fixed test keys.
+    pub fn from_u64(value: u64) -> Result<Self, String> {
+        let mut bytes = [0u8; 32];
+        bytes[24..].copy_from_slice(&value.to_be_bytes());
+        Self::from_bytes(&bytes)
+    }
+
+    /// Secret key derived from at least 32 bytes of input keying material (IETF KeyGen).
+    /// Different key info gives independent keys from the same material.
+    pub fn from_ikm(ikm: &[u8], key_info: &[u8]) -> Result<Self, String> {
+        SecretKey::key_gen(ikm, key_info)
+            .map(Self)
+            .map_err(|err| format!("Key generation failed: {:?}", err))
+    }
+
+    /// Secret key derived from the BIP-39 mnemonic along the EIP-2334 path,
+    /// eg. "m/12381/3600/0/0/0", using the EIP-2333 tree.
+    /// Mnemonic and passphrase are NFKD normalized first, as per BIP-39.
+    pub fn from_mnemonic(mnemonic: &str, passphrase: &str, path: &str) -> Result<Self, String> {
+        let mnemonic = bip39::Mnemonic::parse_normalized(&mnemonic.nfkd().collect::<String>())
+            .map_err(|err| format!("Invalid mnemonic: {}", err))?;
+        let passphrase = passphrase.nfkd().collect::<String>();
+        Self::derive_eip2333(&mnemonic.to_seed_normalized(&passphrase), path)
+    }
+
+    /// Secret key derived from the seed along the path, as per EIP-2333.
+    pub fn derive_eip2333(seed: &[u8], path: &str) -> Result<Self, String> {
+        let mut nodes = path.split('/');
+        if nodes.next() != Some("m") {
+            return Err(format!("Path '{}' must start with 'm'", path));
+        }
+        let master = SecretKey::derive_master_eip2333(seed)
+            .map_err(|err| format!("Master key derivation failed: {:?}", err))?;
+        nodes
+            .try_fold(master, |key, node| {
+                node.parse::<u32>()
+                    .map(|index| key.derive_child_eip2333(index))
+                    .map_err(|err| format!("Invalid path index '{}': {}", node, err))
+            })
+            .map(Self)
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.to_bytes()
+    }
+
+    pub fn public_key(&self) -> Bls12381G1PublicKey {
+        Bls12381G1PublicKey(self.0.sk_to_pk().compress())
+    }
+
+    pub fn sign(&self, msg: &[u8]) -> Bls12381G2Signature {
+        Bls12381G2Signature(self.0.sign(msg, BLS_SIG_DST, &[]).compress())
+    }
+
+    /// Proof of possession: signature over the own public key with the PoP DST.
+    pub fn pop_prove(&self) -> Bls12381G2Signature {
+        Bls12381G2Signature(
+            self.0
+                .sign(&self.public_key().0, BLS_POP_DST, &[])
+                .compress(),
+        )
+    }
+}
+
+impl FromStr for BlsSecretKey {
+    type Err = String;
+
+    /// Secret key from the hex-encoded string
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = hex::decode(s).map_err(|err| format!("Invalid secret key hex: {}", err))?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+/// Decompress and validate the public key (on curve, in subgroup, not infinity).
+fn to_blst_public_key(pub_key: &Bls12381G1PublicKey) -> Result<PublicKey, BLST_ERROR> {
+    PublicKey::key_validate(&pub_key.0)
+}
+
+/// Decompress and validate the signature (on curve, in subgroup, not infinity).
+fn to_blst_signature(signature: &Bls12381G2Signature) -> Result<Signature, BLST_ERROR> {
+    Signature::sig_validate(&signature.0, true)
+}
+
+/// Verify the signature of the message.
+pub fn verify(msg: &[u8], pub_key: &Bls12381G1PublicKey, signature: &Bls12381G2Signature) -> bool {
+    let (Ok(pub_key), Ok(signature)) = (to_blst_public_key(pub_key), to_blst_signature(signature))
+    else {
+        return false;
+    };
+    signature.verify(false, msg, BLS_SIG_DST, &[], &pub_key, false) == BLST_ERROR::BLST_SUCCESS
+}
+
+/// Verify the aggregated signature of the same message signed by all the keys.
+/// It is safe only if the possession of every key has been proven, see [`pop_verify`].
+pub fn fast_aggregate_verify(
+    msg: &[u8],
+    pub_keys: &[Bls12381G1PublicKey],
+    signature: &Bls12381G2Signature,
+) -> bool {
+    let Ok(pub_keys) = pub_keys
+        .iter()
+        .map(to_blst_public_key)
+        .collect::<Result<Vec<_>, _>>()
+    else {
+        return false;
+    };
+    let Ok(signature) = to_blst_signature(signature) else {
+        return false;
+    };
+    let pub_keys: Vec<&PublicKey> = pub_keys.iter().collect();
+    signature.fast_aggregate_verify(false, msg, BLS_SIG_DST, &pub_keys) == BLST_ERROR::BLST_SUCCESS
+}
+
+/// Verify the proof of possession of the secret key of the given public key.
+pub fn pop_verify(pub_key: &Bls12381G1PublicKey, pop: &Bls12381G2Signature) -> bool {
+    let (Ok(blst_pub_key), Ok(pop)) = (to_blst_public_key(pub_key), to_blst_signature(pop)) else {
+        return false;
+    };
+    pop.verify(false, &pub_key.0, BLS_POP_DST, &[], &blst_pub_key, false)
+        == BLST_ERROR::BLST_SUCCESS
+}
+
+/// Check that every public key comes with a valid proof of possession, which protects
+/// fast aggregate verification against rogue-key attacks.
+/// Fails listing the public keys whose proofs are invalid.
+pub fn check_pops(
+    pub_keys: &[Bls12381G1PublicKey],
+    pops: &[Bls12381G2Signature],
+) -> Result<(), String> {
+    if pub_keys.len() != pops.len() {
+        return Err(format!(
+            "Expected one proof of possession per public key, got {} for {} keys",
+            pops.len(),
+            pub_keys.len()
+        ));
+    }
+    let invalid: Vec<String> = pub_keys
+        .iter()
+        .zip(pops)
+        .filter(|(pub_key, pop)| !pop_verify(pub_key, pop))
+        .map(|(pub_key, _)| pub_key.to_string())
+        .collect();
+    if !invalid.is_empty() {
+        return Err(format!(
+            "Invalid proof of possession of public keys: {}",
+            invalid.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+/// Aggregate the public keys, so that [`verify`] with the aggregate key is equivalent to
+/// [`fast_aggregate_verify`] with the keys.
+pub fn aggregate_public_keys(
+    pub_keys: &[Bls12381G1PublicKey],
+) -> Result<Bls12381G1PublicKey, String> {
+    if pub_keys.is_empty() {
+        return Err("No public keys to aggregate".to_string());
+    }
+    let pub_keys = pub_keys
+        .iter()
+        .map(|pub_key| {
+            to_blst_public_key(pub_key)
+                .map_err(|err| format!("Invalid public key {}: {:?}", pub_key, err))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let pub_keys: Vec<&PublicKey> = pub_keys.iter().collect();
+    let aggregate = AggregatePublicKey::aggregate(&pub_keys, false)
+        .map_err(|err| format!("Public key aggregation failed: {:?}", err))?;
+    Ok(Bls12381G1PublicKey(aggregate.to_public_key().compress()))
+}
+
+/// Aggregate the signatures, off-ledger counterpart of bls12381_g2_signature_aggregate.
+pub fn aggregate_signatures(
+    signatures: &[Bls12381G2Signature],
+) -> Result<Bls12381G2Signature, String> {
+    if signatures.is_empty() {
+        return Err("No signatures to aggregate".to_string());
+    }
+    let signatures = signatures
+        .iter()
+        .map(|signature| {
+            to_blst_signature(signature)
+                .map_err(|err| format!("Invalid signature {}: {:?}", signature, err))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let signatures: Vec<&Signature> = signatures.iter().collect();
+    let aggregate = AggregateSignature::aggregate(&signatures, false)
+        .map_err(|err| format!("Signature aggregation failed: {:?}", err))?;
+    Ok(Bls12381G2Signature(aggregate.to_signature().compress()))
+}
+
+/// Rogue public key `attacker_pk - victim_pk`, which aggregated with the victim key gives
+/// the attacker key. It lets the attacker alone forge a fast aggregate signature "signed"
+/// by both keys, although nobody knows the secret key of the rogue public key.
+pub fn rogue_public_key(
+    attacker: &BlsSecretKey,
+    victim: &Bls12381G1PublicKey,
+) -> Result<Bls12381G1PublicKey, String> {
+    let attacker = decompress_g1(&attacker.public_key())?;
+    let mut victim = decompress_g1(victim)?;
+    let mut rogue = blst_p1::default();
+    let mut bytes = [0u8; Bls12381G1PublicKey::LENGTH];
+    unsafe {
+        blst_p1_cneg(&mut victim, true);
+        blst_p1_add_or_double(&mut rogue, &attacker, &victim);
+        blst_p1_compress(bytes.as_mut_ptr(), &rogue);
+    }
+    Ok(Bls12381G1PublicKey(bytes))
+}
+
+fn decompress_g1(pub_key: &Bls12381G1PublicKey) -> Result<blst_p1, String> {
+    let mut affine = blst_p1_affine::default();
+    let mut point = blst_p1::default();
+    unsafe {
+        match blst_p1_uncompress(&mut affine, pub_key.0.as_ptr()) {
+            BLST_ERROR::BLST_SUCCESS => blst_p1_from_affine(&mut point, &affine),
+            err => return Err(format!("Invalid public key {}: {:?}", pub_key, err)),
+        }
+    }
+    Ok(point)
+}
+
+/// Group of the BLS12-381 curve point: public keys are in G1, signatures in G2.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PointGroup {
+    G1,
+    G2,
+}
+
+/// Curve point decoded from any supported encoding, with its validity.
+#[derive(Clone, Debug)]
+pub struct PointInfo {
+    pub group: PointGroup,
+    /// Whether the input was uncompressed
+    pub was_uncompressed: bool,
+    pub compressed: Vec<u8>,
+    pub uncompressed: Vec<u8>,
+    pub in_subgroup: bool,
+    pub is_infinity: bool,
+}
+
+/// Decode bytes given as hex (optionally 0x-prefixed) or base64 and check their length.
+/// Base64 is tried only if the input is not valid hex, as hex digits are valid base64 too.
+pub fn decode_point_bytes(s: &str, expected_lengths: &[usize]) -> Result<Vec<u8>, String> {
+    let s = s.trim();
+    let hex_str = s
+        .strip_prefix("0x")
+        .or_else(|| s.strip_prefix("0X"))
+        .unwrap_or(s);
+    let is_prefixed = hex_str.len() != s.len();
+    let (bytes, encoding) = match hex::decode(hex_str) {
+        Ok(bytes) => (bytes, "hex"),
+        Err(err) if is_prefixed => return Err(format!("Invalid hex '{}': {}", s, err)),
+        Err(_) => match base64::engine::general_purpose::STANDARD.decode(s) {
+            Ok(bytes) => (bytes, "base64"),
+            Err(_) => return Err(format!("'{}' is neither hex nor base64", s)),
+        },
+    };
+    if !expected_lengths.contains(&bytes.len()) {
+        return Err(format!(
+            "Invalid point length {} ({}-encoded), expected one of {:?}",
+            bytes.len(),
+            encoding,
+            expected_lengths
+        ));
+    }
+    Ok(bytes)
+}
+
+/// Parse the public key given as compressed or uncompressed point, encoded as hex,
+/// 0x-prefixed hex or base64. Compressed points are taken as they are, without validation.
+pub fn parse_public_key(s: &str) -> Result<Bls12381G1PublicKey, String> {
+    let bytes = decode_point_bytes(
+        s,
+        &[Bls12381G1PublicKey::LENGTH, 2 * Bls12381G1PublicKey::LENGTH],
+    )?;
+    let compressed = match bytes.len() {
+        Bls12381G1PublicKey::LENGTH => bytes,
+        _ => match inspect_point(&bytes)? {
+            PointInfo {
+                group: PointGroup::G1,
+                compressed,
+                ..
+            } => compressed,
+            _ => return Err(format!("'{}' is not a G1 point", s)),
+        },
+    };
+    Ok(Bls12381G1PublicKey(compressed.try_into().unwrap()))
+}
+
+/// Parse the signature given as compressed or uncompressed point, encoded as hex,
+/// 0x-prefixed hex or base64. Compressed points are taken as they are, without validation.
+pub fn parse_signature(s: &str) -> Result<Bls12381G2Signature, String> {
+    let bytes = decode_point_bytes(
+        s,
+        &[Bls12381G2Signature::LENGTH, 2 * Bls12381G2Signature::LENGTH],
+    )?;
+    let compressed = match bytes.len() {
+        Bls12381G2Signature::LENGTH if is_compressed(&bytes) => bytes,
+        _ => match inspect_point(&bytes)? {
+            PointInfo {
+                group: PointGroup::G2,
+                compressed,
+                ..
+            } => compressed,
+            _ => return Err(format!("'{}' is not a G2 point", s)),
+        },
+    };
+    Ok(Bls12381G2Signature(compressed.try_into().unwrap()))
+}
+
+/// Decode the public key or signature point given in any supported encoding,
+/// so that it can be converted to the other ones.
+/// Fails if the point is not on the curve or not in its subgroup.
+pub fn convert_point(s: &str) -> Result<PointInfo, String> {
+    let bytes = decode_point_bytes(
+        s,
+        &[
+            Bls12381G1PublicKey::LENGTH,
+            Bls12381G2Signature::LENGTH,
+            2 * Bls12381G2Signature::LENGTH,
+        ],
+    )?;
+    let info = inspect_point(&bytes)?;
+    if !info.in_subgroup {
+        return Err(format!("Point is not in the {:?} subgroup", info.group));
+    }
+    Ok(info)
+}
+
+// The most significant bit is the compression flag
+fn is_compressed(bytes: &[u8]) -> bool {
+    bytes.first().map_or(false, |byte| byte & 0x80 != 0)
+}
+
+/// Decode the point and check it. The group is inferred from the length and compression flag:
+/// 48 bytes compressed G1, 96 bytes uncompressed G1 or compressed G2, 192 bytes uncompressed G2.
+/// Fails if the point is not on the curve.
+pub fn inspect_point(bytes: &[u8]) -> Result<PointInfo, String> {
+    let was_uncompressed = !is_compressed(bytes);
+    let group = match (bytes.len(), was_uncompressed) {
+        (48, false) | (96, true) => PointGroup::G1,
+        (96, false) | (192, true) => PointGroup::G2,
+        (len, _) => {
+            return Err(format!(
+                "Invalid point length {} (compressed: {})",
+                len, !was_uncompressed
+            ))
+        }
+    };
+
+    let (compressed, uncompressed, in_subgroup, is_infinity) = match group {
+        PointGroup::G1 => {
+            let mut affine = blst_p1_affine::default();
+            let mut compressed = vec![0u8; 48];
+            let mut uncompressed = vec![0u8; 96];
+            unsafe {
+                match blst_p1_deserialize(&mut affine, bytes.as_ptr()) {
+                    BLST_ERROR::BLST_SUCCESS => {}
+                    err => return Err(format!("Invalid G1 point: {:?}", err)),
+                }
+                blst_p1_affine_compress(compressed.as_mut_ptr(), &affine);
+                blst_p1_affine_serialize(uncompressed.as_mut_ptr(), &affine);
+                let in_subgroup = blst_p1_affine_in_g1(&affine);
+                let is_infinity = blst_p1_affine_is_inf(&affine);
+                (compressed, uncompressed, in_subgroup, is_infinity)
+            }
+        }
+        PointGroup::G2 => {
+            let mut affine = blst_p2_affine::default();
+            let mut compressed = vec![0u8; 96];
+            let mut uncompressed = vec![0u8; 192];
+            unsafe {
+                match blst_p2_deserialize(&mut affine, bytes.as_ptr()) {
+                    BLST_ERROR::BLST_SUCCESS => {}
+                    err => return Err(format!("Invalid G2 point: {:?}", err)),
+                }
+                blst_p2_affine_compress(compressed.as_mut_ptr(), &affine);
+                blst_p2_affine_serialize(uncompressed.as_mut_ptr(), &affine);
+                let in_subgroup = blst_p2_affine_in_g2(&affine);
+                let is_infinity = blst_p2_affine_is_inf(&affine);
+                (compressed, uncompressed, in_subgroup, is_infinity)
+            }
+        }
+    };
+
+    Ok(PointInfo {
+        group,
+        was_uncompressed,
+        compressed,
+        uncompressed,
+        in_subgroup,
+        is_infinity,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rogue_key_forgery_is_caught_by_pop_check() {
+        let msg = b"Hello World!";
+        let victim = BlsSecretKey::from_u64(1).unwrap();
+        let attacker = BlsSecretKey::from_u64(2).unwrap();
+        let rogue = rogue_public_key(&attacker, &victim.public_key()).unwrap();
+        let pub_keys = [victim.public_key(), rogue];
+
+        // Signed by the attacker alone, yet valid for both keys
+        assert!(fast_aggregate_verify(msg, &pub_keys, &attacker.sign(msg)));
+
+        // Nobody knows the rogue secret key, so the attacker can only present its own PoP
+        let pops = [victim.pop_prove(), attacker.pop_prove()];
+        assert!(!pop_verify(&rogue, &attacker.pop_prove()));
+        assert!(check_pops(&pub_keys, &pops).is_err());
+
+        // Honest keys with their own PoPs pass the check
+        let honest_pub_keys = [victim.public_key(), attacker.public_key()];
+        assert!(check_pops(&honest_pub_keys, &pops).is_ok());
+    }
+
+    fn assert_decodes_to<T: PartialEq + fmt::Debug>(
+        parse: fn(&str) -> Result<T, String>,
+        compressed: &[u8],
+        expected: T,
+    ) {
+        let engine = base64::engine::general_purpose::STANDARD;
+        let uncompressed = convert_point(&hex::encode(compressed))
+            .unwrap()
+            .uncompressed;
+        let encodings = [
+            hex::encode(compressed),
+            format!("0x{}", hex::encode(compressed)),
+            format!(" 0X{} ", hex::encode_upper(compressed)),
+            engine.encode(compressed),
+            hex::encode(&uncompressed),
+            format!("0x{}", hex::encode(&uncompressed)),
+            engine.encode(&uncompressed),
+        ];
+        for encoding in encodings {
+            assert_eq!(parse(&encoding).unwrap(), expected, "{}", encoding);
+            assert_eq!(convert_point(&encoding).unwrap().compressed, compressed);
+        }
+    }
+
+    #[test]
+    fn parse_public_key_encodings() {
+        let pub_key = BlsSecretKey::from_u64(1).unwrap().public_key();
+        assert_decodes_to(parse_public_key, &pub_key.0, pub_key);
+    }
+
+    #[test]
+    fn parse_signature_encodings() {
+        let signature = BlsSecretKey::from_u64(1).unwrap().sign(b"Hello World!");
+        assert_decodes_to(parse_signature, &signature.0, signature);
+    }
+
+    #[test]
+    fn point_length_checked_for_any_encoding() {
+        let engine = base64::engine::general_purpose::STANDARD;
+        let bytes = [0xc0u8; 47];
+        for encoding in [
+            hex::encode(bytes),
+            format!("0x{}", hex::encode(bytes)),
+            engine.encode(bytes),
+        ] {
+            let err = parse_public_key(&encoding).unwrap_err();
+            assert!(err.starts_with("Invalid point length 47"), "{}", err);
+        }
+        assert!(parse_public_key("0xzz").is_err());
+        assert!(parse_public_key("not a point!").is_err());
+    }
+
+    #[test]
+    fn convert_rejects_points_outside_subgroup() {
+        for point in [
+            crate::edge_cases::g1_not_in_subgroup(),
+            crate::edge_cases::g2_not_in_subgroup(),
+        ] {
+            let info = inspect_point(&point).unwrap();
+            assert!(!info.in_subgroup);
+            let err = convert_point(&hex::encode(&point)).unwrap_err();
+            assert!(err.contains("not in the"), "{}", err);
+        }
+    }
+
+    // EIP-2333 test cases: seed, master secret key, child index, child secret key
+    const EIP2333_VECTORS: [(&str, &str, u32, &str); 4] = [
+        (
+            "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04",
+            "0d7359d57963ab8fbbde1852dcf553fedbc31f464d80ee7d40ae683122b45070",
+            0,
+            "2d18bd6c14e6d15bf8b5085c9b74f3daae3b03cc2014770a599d8c1539e50f8e",
+        ),
+        (
+            "3141592653589793238462643383279502884197169399375105820974944592",
+            "41c9e07822b092a93fd6797396338c3ada4170cc81829fdfce6b5d34bd5e7ec7",
+            3141592653,
+            "384843fad5f3d777ea39de3e47a8f999ae91f89e42bffa993d91d9782d152a0f",
+        ),
+        (
+            "0099ff991111002299dd7744ee3355bbdd8844115566cc55663355668888cc00",
+            "3cfa341ab3910a7d00d933d8f7c4fe87c91798a0397421d6b19fd5b815132e80",
+            4294967295,
+            "40e86285582f35b28821340f6a53b448588efa575bc4d88c32ef8567b8d9479b",
+        ),
+        (
+            "d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3",
+            "2a0e28ffa5fbbe2f8e7aad4ed94f745d6bf755c51182e119bb1694fe61d3afca",
+            42,
+            "455c0dc9fccb3395825d92a60d2672d69416be1c2578a87a7a3d3ced11ebb88d",
+        ),
+    ];
+
+    #[test]
+    fn derive_eip2333_spec_vectors() {
+        for (seed, master, index, child) in EIP2333_VECTORS {
+            let seed = hex::decode(seed).unwrap();
+            let master_key = BlsSecretKey::derive_eip2333(&seed, "m").unwrap();
+            assert_eq!(hex::encode(master_key.to_bytes()), master);
+            let child_key = BlsSecretKey::derive_eip2333(&seed, &format!("m/{}", index)).unwrap();
+            assert_eq!(hex::encode(child_key.to_bytes()), child);
+        }
+    }
+
+    #[test]
+    fn derive_eip2333_invalid_path() {
+        let seed = [7u8; 32];
+        assert!(BlsSecretKey::derive_eip2333(&seed, "12381/3600").is_err());
+        assert!(BlsSecretKey::derive_eip2333(&seed, "m/12381/x").is_err());
+        assert!(BlsSecretKey::derive_eip2333(&seed, "m/4294967296").is_err());
+    }
+
+    #[test]
+    fn mnemonic_passphrase_is_normalized() {
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let path = "m/12381/3600/0/0/0";
+        // "é" composed (NFC) and decomposed (NFD) derive the same key
+        let composed = BlsSecretKey::from_mnemonic(mnemonic, "caf\u{e9}", path).unwrap();
+        let decomposed = BlsSecretKey::from_mnemonic(mnemonic, "cafe\u{301}", path).unwrap();
+        assert_eq!(composed.to_bytes(), decomposed.to_bytes());
+
+        let other = BlsSecretKey::from_mnemonic(mnemonic, "cafe", path).unwrap();
+        assert_ne!(composed.to_bytes(), other.to_bytes());
+    }
+}
@@ -1,24 +1,35 @@
-use crate::gateway::*;
-use crate::utils::*;
-use clap::{Parser, Subcommand};
+use base64::Engine;
+use bls_tests::bls::{self, BlsSecretKey, Prehash};
+use bls_tests::client::*;
+use bls_tests::compat::*;
+use bls_tests::crypto_scrypto::*;
+use bls_tests::edge_cases::*;
+use bls_tests::gateway::*;
+use bls_tests::keystore::Keystore;
+use bls_tests::load::*;
+use bls_tests::threshold::*;
+use bls_tests::utils::*;
+use clap::{Args, Parser, Subcommand};
 use scrypto::blueprints::package::PackageDefinition;
+use serde::Deserialize;
 use std::fs;
+use std::io::{self, Read};
 use std::{thread, time};
 use transaction::prelude::*;
+use transaction::validation::{NotarizedTransactionValidator, ValidationConfig};
 
 // Enkinet network data
 const NETWORK_ID: u8 = 0x21;
 const NETWORK_NAME: &str = "enkinet";
 const NETWORK_HRP_SUFFIX: &str = "tdx_21_";
-const GATEWAY_URL: &str = "https://enkinet-gateway.radixdlt.com";
+// Gateways are tried in the given order, until a healthy one is found
+const GATEWAY_URLS: &[&str] = &["https://enkinet-gateway.radixdlt.com"];
 
 // Mardunet network data
 const MARDUNET_NETWORK_ID: u8 = 0x24;
 const MARDUNET_NETWORK_NAME: &str = "mardunet";
 const MARDUNET_NETWORK_HRP_SUFFIX: &str = "tdx_24_";
-const MARDUNET_GATEWAY_URL: &str = "https://mardunet-gateway.radixdlt.com";
-
-const CRYPTO_SCRYPTO_BLUEPRINT_NAME: &str = "CryptoScrypto";
+const MARDUNET_GATEWAY_URLS: &[&str] = &["https://mardunet-gateway.radixdlt.com"];
 
 // This is the package address of the published CryptoScrypto blueprint.
 // If you publish it by yourself you can use the new adress as well.
@@ -27,6 +38,8 @@ const CRYPTO_SCRYPTO_PACKAGE_ADDRESS: &str =
 
 const TEST_MSG1: &str = "Hello World!";
 const TEST_MSG2: &str = "Goodbye World!";
+// EIP-2334 path of the first validator signing key
+const DEFAULT_DERIVATION_PATH: &str = "m/12381/3600/0/0/0";
 // Below key is derived from secret key: 5B00CC8C7153F39EF2E6E2FADB1BB95A1F4BF21F43CC5B28EFA9E526FB788C08
 const TEST_PUB_KEY1: &str = "8a38419cb83c15a92d11243384bea0acd15cbacc24b385b9c577b17272d6ad68bb53c52dbbf79324005528d2d73c2643";
 
@@ -43,6 +56,10 @@ const TEST_MSG2_SIGNATURE: &str = "a077aff3fbee37fc7a260d4b955626b390d4b0d9604e0
 
 const TEST_AGGREGATE_VERIFY_SIGNATURE: &str = "a9e876f875b09c22f222b9052f339bdfc94529d3f409ed7476885c1c68acab6d02d686b6a9b585b224d3246e844e55911235b1e640b01cc864b2b3e8ddc961e5f2f69f91e8c11c7517f4cdf51cb33a828a1a5f457faef13463b6714735ecccac";
 
+// Secret key of the attacker in the rogue-key demo, it is Bls12381G1PrivateKey::from_u64(3)
+const TEST_ATTACKER_SECRET_KEY: &str =
+    "0000000000000000000000000000000000000000000000000000000000000003";
+
 const TEST_FAST_AGGREGATE_VERIFY_SIGNATURE: &str = "86aab079e63f469ee176982f4ab42d367f2a6ecd6e389f31c4b214c37581f1cb9e71203693364c2fc85e361d28fbdefb03773301363ae1649af0e1a23cfdc65a73ec8c4463bd89399a38de8f9a85d85a504721d96fac2b6df0a17722edc61f6e";
 
 const CRYPTO_SCRYPTO_CODE_PATH: &str = "crypto_scrypto/crypto_scrypto.wasm";
@@ -64,17 +81,81 @@ struct Cli {
     #[arg(long, short, default_value_t = NETWORK_NAME.to_string())]
     /// Switch to mardunet network
     network: String,
+    #[arg(long, conflicts_with = "network")]
+    /// Use given gateway and detect its network, instead of the predefined network
+    gateway_url: Option<String>,
+    #[arg(long, global = true)]
+    /// Do not sign nor submit the transaction, just print its manifest
+    dry_run: bool,
+    #[arg(long, global = true, requires = "dry_run")]
+    /// Write the manifest of the dry run to the given file instead of printing it.
+    /// Manifest blobs are written to the same directory as "<blob hash>.blob" files.
+    emit_manifest: Option<String>,
+    #[arg(long, global = true, default_value_t = 3)]
+    /// Number of retries of gateway requests failing with transport errors, 5xx or 429
+    max_retries: u32,
+    #[arg(long, global = true, default_value_t = 500)]
+    /// Backoff before the first retry, doubled with every next one (milliseconds)
+    retry_backoff: u64,
+    #[arg(long, global = true)]
+    /// Skip the gateway compatibility check performed on startup
+    skip_checks: bool,
+    #[arg(long, global = true)]
+    /// Gateway connection timeout (milliseconds)
+    connect_timeout: Option<u64>,
+    #[arg(long, global = true)]
+    /// Gateway request timeout (milliseconds)
+    request_timeout: Option<u64>,
+    #[arg(long, global = true)]
+    /// Proxy for gateway requests, eg. http://proxy.example.com:8080
+    proxy: Option<String>,
+    #[arg(long, global = true)]
+    /// PEM file with the additional root certificate to trust
+    ca_bundle: Option<String>,
+    #[arg(long, global = true)]
+    /// User agent sent to the gateway
+    user_agent: Option<String>,
+    #[arg(long = "header", global = true, value_parser = parse_header)]
+    /// Header sent with every gateway request, eg. "Authorization: Bearer <token>".
+    /// Can be given multiple times.
+    headers: Vec<(String, String)>,
     #[command(subcommand)]
     command: Commands,
 }
 
+impl Commands {
+    // Whether command talks to the gateway, so it is worth to check it on startup
+    fn uses_gateway(&self) -> bool {
+        match self {
+            Commands::GatewayStatus | Commands::Doctor | Commands::Tx(TxCommands::Decode(_)) => {
+                false
+            }
+            Commands::Bls(BlsCommands::RogueKeyDemo(cmd)) => cmd.submit,
+            Commands::Bls(BlsCommands::EdgeCases(_)) => true,
+            Commands::Bls(_) => false,
+            Commands::Threshold(ThresholdCommands::Combine(cmd)) => cmd.public_key.is_some(),
+            Commands::Threshold(_) => false,
+            _ => true,
+        }
+    }
+}
+
+fn parse_header(s: &str) -> Result<(String, String), String> {
+    match s.split_once(':') {
+        Some((name, value)) => Ok((name.trim().to_string(), value.trim().to_string())),
+        None => Err(format!("Header '{}' must be given as 'Name: value'", s)),
+    }
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Get gateway status. This is sanity check, whether gateway is working fine.
     GatewayStatus,
+    /// Check whether gateway serves the selected network with supported API version
+    Doctor,
     /// Calculate Keccak256 hash over given message
     KeccakHash(KeccakHash),
-    /// Perform BLS verification over Keccak256 hash from given message, public key and signature
+    /// Perform BLS verification over the message digest (Keccak256 by default), public key and signature
     BlsVerify(BlsVerify),
     /// Perform BLS aggregate verification over given messages, public keys and signature
     BlsAggregateVerify(BlsAggregateVerify),
@@ -86,6 +167,163 @@ enum Commands {
     PublishPackage(PublishPackage),
     /// Free XRD from faucet to destination account address
     FreeXrd(FreeXrd),
+    /// Perform multiple CryptoScrypto calls from the JSON file in a single transaction
+    Batch(Batch),
+    /// Run transaction manifest from the file (.rtm format)
+    RunManifest(RunManifest),
+    /// Submit many concurrent BLS verification transactions and report statistics
+    Load(Load),
+    /// Watch committed transactions calling the CryptoScrypto package
+    Watch(Watch),
+    /// Inspect already submitted transactions
+    #[command(subcommand)]
+    Tx(TxCommands),
+    /// Off-ledger BLS operations
+    #[command(subcommand)]
+    Bls(BlsCommands),
+    /// Threshold (t-of-n) BLS signing
+    #[command(subcommand)]
+    Threshold(ThresholdCommands),
+}
+
+#[derive(Subcommand)]
+enum TxCommands {
+    /// Get committed transaction details with optional receipt parts
+    Details(TxDetails),
+    /// Get the status of the transaction
+    Status(TxStatus),
+    /// Get the output of the committed transaction decoded with the expected type
+    Output(TxOutput),
+    /// Validate and decompile the notarized transaction payload
+    Decode(TxDecode),
+}
+
+#[derive(Subcommand)]
+enum BlsCommands {
+    /// Derive BLS key from BIP-39 mnemonic along EIP-2333 path
+    Derive(BlsDerive),
+    /// Sign the message digest (Keccak256 by default), so that it can be checked with bls-verify
+    Sign(BlsSign),
+    /// Encrypt the secret key into EIP-2335 keystore file
+    KeystoreExport(BlsKeystoreExport),
+    /// Decrypt EIP-2335 keystore file and show its key
+    KeystoreImport(BlsKeystoreImport),
+    /// Generate proof of possession (PoP) of the secret key
+    PopProve(BlsPopProve),
+    /// Verify proof of possession (PoP) of the public key.
+    /// It is done off-ledger, as CryptoScrypto blueprint verifies signatures with the message
+    /// domain separation tag only, which cannot be used for PoP.
+    PopVerify(BlsPopVerify),
+    /// Aggregate BLS public keys.
+    /// It is done off-ledger, as CryptoScrypto blueprint has no public key aggregation function.
+    PubkeyAggregate(BlsPubkeyAggregate),
+    /// Normalize BLS public key or signature and check that it is valid curve point
+    Convert(BlsConvert),
+    /// Forge fast aggregate signature with a rogue public key and show that PoP check catches it
+    RogueKeyDemo(BlsRogueKeyDemo),
+    /// Submit malformed and edge-case inputs raw, bypassing client-side validation,
+    /// and record the engine behavior for each case
+    EdgeCases(BlsEdgeCases),
+}
+
+#[derive(Clone, Debug, Args)]
+struct MsgArgs {
+    #[arg(long, short, default_value_t = TEST_MSG1.to_string(), conflicts_with_all = ["msg_hex", "msg_base64", "msg_file"])]
+    /// Message (UTF-8 string)
+    msg: String,
+    #[arg(long, conflicts_with_all = ["msg_base64", "msg_file"])]
+    /// Message (hex-encoded bytes)
+    msg_hex: Option<String>,
+    #[arg(long, conflicts_with = "msg_file")]
+    /// Message (base64-encoded bytes)
+    msg_base64: Option<String>,
+    #[arg(long)]
+    /// File with the message, '-' to read it from stdin
+    msg_file: Option<String>,
+}
+
+impl MsgArgs {
+    // Message bytes from the given source, UTF-8 string by default
+    fn bytes(&self) -> Vec<u8> {
+        match (&self.msg_hex, &self.msg_base64, &self.msg_file) {
+            (Some(msg), _, _) => hex::decode(msg).expect("Invalid message hex"),
+            (_, Some(msg), _) => decode_base64(msg),
+            (_, _, Some(file)) => read_message_file(file),
+            _ => self.msg.clone().into_bytes(),
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+struct MsgsArgs {
+    #[arg(long, short, use_value_delimiter = true, value_delimiter = ',', default_values_t = vec![TEST_MSG1.to_string(), TEST_MSG2.to_string()], conflicts_with_all = ["msgs_hex", "msgs_base64", "msg_files"])]
+    /// Messages (UTF-8 strings separated with commas)
+    msgs: Vec<String>,
+    #[arg(long, use_value_delimiter = true, value_delimiter = ',', conflicts_with_all = ["msgs_base64", "msg_files"])]
+    /// Messages (hex-encoded bytes separated with commas)
+    msgs_hex: Vec<String>,
+    #[arg(
+        long,
+        use_value_delimiter = true,
+        value_delimiter = ',',
+        conflicts_with = "msg_files"
+    )]
+    /// Messages (base64-encoded bytes separated with commas)
+    msgs_base64: Vec<String>,
+    #[arg(long = "msg-file")]
+    /// File with the message, one message per file, '-' to read it from stdin.
+    /// Can be given multiple times.
+    msg_files: Vec<String>,
+}
+
+impl MsgsArgs {
+    // Messages bytes from the given source, UTF-8 strings by default
+    fn bytes(&self) -> Vec<Vec<u8>> {
+        if !self.msgs_hex.is_empty() {
+            self.msgs_hex
+                .iter()
+                .map(|msg| hex::decode(msg).expect("Invalid message hex"))
+                .collect()
+        } else if !self.msgs_base64.is_empty() {
+            self.msgs_base64
+                .iter()
+                .map(|msg| decode_base64(msg))
+                .collect()
+        } else if !self.msg_files.is_empty() {
+            assert!(
+                self.msg_files.iter().filter(|file| *file == "-").count() <= 1,
+                "Only one message can be read from stdin"
+            );
+            self.msg_files
+                .iter()
+                .map(|file| read_message_file(file))
+                .collect()
+        } else {
+            self.msgs
+                .iter()
+                .map(|msg| msg.clone().into_bytes())
+                .collect()
+        }
+    }
+}
+
+fn decode_base64(msg: &str) -> Vec<u8> {
+    base64::engine::general_purpose::STANDARD
+        .decode(msg)
+        .expect("Invalid message base64")
+}
+
+// Read the whole file or stdin if file is "-"
+fn read_message_file(file: &str) -> Vec<u8> {
+    if file == "-" {
+        let mut msg = vec![];
+        io::stdin()
+            .read_to_end(&mut msg)
+            .expect("Cannot read message from stdin");
+        msg
+    } else {
+        fs::read(file).unwrap_or_else(|err| panic!("Cannot read message file {}: {}", file, err))
+    }
 }
 
 #[derive(Debug, Parser)]
@@ -93,9 +331,8 @@ struct KeccakHash {
     #[arg(long, short = 'a', default_value_t = CRYPTO_SCRYPTO_PACKAGE_ADDRESS.to_string())]
     /// Package address of the CryptoScrypto blueprint
     package_address: String,
-    #[arg(long, short, default_value_t = TEST_MSG1.to_string())]
-    /// Message to hash
-    msg: String,
+    #[command(flatten)]
+    msg: MsgArgs,
 }
 
 #[derive(Debug, Parser)]
@@ -103,15 +340,17 @@ struct BlsVerify {
     #[arg(long, short = 'a', default_value_t = CRYPTO_SCRYPTO_PACKAGE_ADDRESS.to_string())]
     /// Package address of the CryptoScrypto blueprint
     package_address: String,
-    #[arg(long, short, default_value_t = TEST_MSG1.to_string())]
-    /// Message to verify signature with (it will be hashed before with Keccak256)
-    msg: String,
+    #[command(flatten)]
+    msg: MsgArgs,
     #[arg(long, short, default_value_t = TEST_PUB_KEY1.to_string())]
-    /// BLS public key to perform verification (hex-encoded string)
+    /// BLS public key to perform verification (hex or base64, compressed or uncompressed)
     public_key: String,
-    /// BLS signature to verify (hex-encoded string)
+    /// BLS signature to verify (hex or base64, compressed or uncompressed)
     #[arg(long, short, default_value_t = TEST_KCK_MSG1_SIGNATURE.to_string())]
     signature: String,
+    #[arg(long, default_value_t = Prehash::Keccak256)]
+    /// Hash applied to the message: keccak256, sha256, blake2b or none
+    prehash: Prehash,
 }
 
 #[derive(Debug, Parser)]
@@ -119,15 +358,17 @@ struct BlsAggregateVerify {
     #[arg(long, short = 'a', default_value_t = CRYPTO_SCRYPTO_PACKAGE_ADDRESS.to_string())]
     /// Package address of the CryptoScrypto blueprint
     package_address: String,
-    #[arg(long, short, use_value_delimiter = true, value_delimiter = ',', default_values_t = vec![TEST_MSG1.to_string(), TEST_MSG2.to_string()])]
-    /// Messages to verify signature with
-    msgs: Vec<String>,
+    #[command(flatten)]
+    msgs: MsgsArgs,
     #[arg(long, short, use_value_delimiter = true, value_delimiter = ',', default_values_t = vec![TEST_PUB_KEY1.to_string(), TEST_PUB_KEY2.to_string()])]
-    /// BLS public key to perform verification (hex-encoded string)
+    /// BLS public key to perform verification (hex or base64, compressed or uncompressed)
     public_keys: Vec<String>,
-    /// BLS signature to verify (hex-encoded string)
+    /// BLS signature to verify (hex or base64, compressed or uncompressed)
     #[arg(long, short, default_value_t = TEST_AGGREGATE_VERIFY_SIGNATURE.to_string())]
     signature: String,
+    #[arg(long, default_value_t = Prehash::None)]
+    /// Hash applied to the messages: keccak256, sha256, blake2b or none
+    prehash: Prehash,
 }
 
 #[derive(Debug, Parser)]
@@ -135,15 +376,23 @@ struct BlsFastAggregateVerify {
     #[arg(long, short = 'a', default_value_t = CRYPTO_SCRYPTO_PACKAGE_ADDRESS.to_string())]
     /// Package address of the CryptoScrypto blueprint
     package_address: String,
-    #[arg(long, short, default_value_t = TEST_MSG1.to_string())]
-    /// Message to verify signature with
-    msg: String,
+    #[command(flatten)]
+    msg: MsgArgs,
     #[arg(long, short, use_value_delimiter = true, value_delimiter = ',', default_values_t = vec![TEST_PUB_KEY1.to_string(), TEST_PUB_KEY2.to_string()])]
-    /// BLS public key to perform verification (hex-encoded string)
+    /// BLS public key to perform verification (hex or base64, compressed or uncompressed)
     public_keys: Vec<String>,
-    /// BLS signature to verify (hex-encoded string)
+    /// BLS signature to verify (hex or base64, compressed or uncompressed)
     #[arg(long, short, default_value_t = TEST_FAST_AGGREGATE_VERIFY_SIGNATURE.to_string())]
     signature: String,
+    #[arg(long, requires = "pops")]
+    /// Refuse to submit unless proof of possession of every public key is valid
+    require_pop: bool,
+    #[arg(long, use_value_delimiter = true, value_delimiter = ',')]
+    /// Proofs of possession of the public keys, in the same order (hex or base64, compressed or uncompressed)
+    pops: Vec<String>,
+    #[arg(long, default_value_t = Prehash::None)]
+    /// Hash applied to the message: keccak256, sha256, blake2b or none
+    prehash: Prehash,
 }
 
 #[derive(Debug, Parser)]
@@ -151,7 +400,7 @@ struct BlsSignatureAggregate {
     #[arg(long, short = 'a', default_value_t = CRYPTO_SCRYPTO_PACKAGE_ADDRESS.to_string())]
     /// Package address of the CryptoScrypto blueprint
     package_address: String,
-    /// BLS signatures to aggregate (hex-encoded string)
+    /// BLS signatures to aggregate (hex or base64, compressed or uncompressed)
     #[arg(long, short, use_value_delimiter = true, value_delimiter = ',', default_values_t = vec![TEST_MSG1_SIGNATURE.to_string(), TEST_MSG2_SIGNATURE.to_string()])]
     signatures: Vec<String>,
 }
@@ -175,6 +424,633 @@ struct FreeXrd {
     account_address: String,
 }
 
+#[derive(Debug, Parser)]
+struct Batch {
+    #[arg(long, short = 'a', default_value_t = CRYPTO_SCRYPTO_PACKAGE_ADDRESS.to_string())]
+    /// Package address of the CryptoScrypto blueprint
+    package_address: String,
+    /// JSON file with the list of calls, eg.
+    /// [
+    ///   { "function": "keccak256_hash", "msg": "Hello World!" },
+    ///   { "function": "bls12381_v1_verify", "msg": "...", "public_key": "...", "signature": "..." },
+    ///   { "function": "bls12381_v1_aggregate_verify", "msgs": [...], "public_keys": [...], "signature": "..." },
+    ///   { "function": "bls12381_v1_fast_aggregate_verify", "msg": "...", "public_keys": [...], "signature": "..." },
+    ///   { "function": "bls12381_g2_signature_aggregate", "signatures": [...] }
+    /// ]
+    /// Verify calls take optional "prehash" (keccak256, sha256, blake2b or none), which
+    /// defaults to keccak256 for bls12381_v1_verify and to none for the aggregate ones.
+    #[arg(verbatim_doc_comment)]
+    calls_path: String,
+}
+
+// Single call of the batch, arguments are the same as for the corresponding subcommands
+#[derive(Debug, Deserialize)]
+#[serde(tag = "function", rename_all = "snake_case")]
+enum BatchCall {
+    Keccak256Hash {
+        msg: String,
+    },
+    Bls12381V1Verify {
+        msg: String,
+        public_key: String,
+        signature: String,
+        prehash: Option<Prehash>,
+    },
+    Bls12381V1AggregateVerify {
+        msgs: Vec<String>,
+        public_keys: Vec<String>,
+        signature: String,
+        prehash: Option<Prehash>,
+    },
+    Bls12381V1FastAggregateVerify {
+        msg: String,
+        public_keys: Vec<String>,
+        signature: String,
+        prehash: Option<Prehash>,
+    },
+    Bls12381G2SignatureAggregate {
+        signatures: Vec<String>,
+    },
+}
+
+impl BatchCall {
+    fn function(&self) -> CryptoScryptoFunction {
+        match self {
+            Self::Keccak256Hash { .. } => CryptoScryptoFunction::Keccak256Hash,
+            Self::Bls12381V1Verify { .. } => CryptoScryptoFunction::Bls12381V1Verify,
+            Self::Bls12381V1AggregateVerify { .. } => {
+                CryptoScryptoFunction::Bls12381V1AggregateVerify
+            }
+            Self::Bls12381V1FastAggregateVerify { .. } => {
+                CryptoScryptoFunction::Bls12381V1FastAggregateVerify
+            }
+            Self::Bls12381G2SignatureAggregate { .. } => {
+                CryptoScryptoFunction::Bls12381G2SignatureAggregate
+            }
+        }
+    }
+
+    // Append the call_function instruction for this call to the manifest,
+    // with the input of the type generated from the package definition
+    fn add_to_manifest(
+        &self,
+        builder: ManifestBuilder,
+        package_address: PackageAddress,
+    ) -> ManifestBuilder {
+        match self {
+            Self::Keccak256Hash { msg } => {
+                let input = Keccak256HashInput {
+                    data: msg.as_bytes().to_vec(),
+                };
+                builder.call_crypto_scrypto(package_address, &input)
+            }
+            Self::Bls12381V1Verify {
+                msg,
+                public_key,
+                signature,
+                prehash,
+            } => {
+                // Message is hashed with Keccak256 by default as in BlsVerify subcommand
+                let prehash = prehash.unwrap_or(Prehash::Keccak256);
+                let input = Bls12381V1VerifyInput {
+                    message: prehash.digest(msg.as_bytes()),
+                    pub_key: bls::parse_public_key(public_key).unwrap(),
+                    signature: bls::parse_signature(signature).unwrap(),
+                };
+                builder.call_crypto_scrypto(package_address, &input)
+            }
+            Self::Bls12381V1AggregateVerify {
+                msgs,
+                public_keys,
+                signature,
+                prehash,
+            } => {
+                let prehash = prehash.unwrap_or(Prehash::None);
+                assert_eq!(
+                    msgs.len(),
+                    public_keys.len(),
+                    "Expected the same number of messages and public keys"
+                );
+                let input = Bls12381V1AggregateVerifyInput {
+                    pub_keys_msgs: public_keys
+                        .iter()
+                        .zip(msgs)
+                        .map(|(pk, msg)| {
+                            let digest = prehash.digest(msg.as_bytes());
+                            (bls::parse_public_key(pk).unwrap(), digest)
+                        })
+                        .collect(),
+                    signature: bls::parse_signature(signature).unwrap(),
+                };
+                builder.call_crypto_scrypto(package_address, &input)
+            }
+            Self::Bls12381V1FastAggregateVerify {
+                msg,
+                public_keys,
+                signature,
+                prehash,
+            } => {
+                let prehash = prehash.unwrap_or(Prehash::None);
+                let input = Bls12381V1FastAggregateVerifyInput {
+                    message: prehash.digest(msg.as_bytes()),
+                    pub_keys: public_keys
+                        .iter()
+                        .map(|pk| bls::parse_public_key(pk).unwrap())
+                        .collect(),
+                    signature: bls::parse_signature(signature).unwrap(),
+                };
+                builder.call_crypto_scrypto(package_address, &input)
+            }
+            Self::Bls12381G2SignatureAggregate { signatures } => {
+                let input = Bls12381G2SignatureAggregateInput {
+                    signatures: signatures
+                        .iter()
+                        .map(|s| bls::parse_signature(s).unwrap())
+                        .collect(),
+                };
+                builder.call_crypto_scrypto(package_address, &input)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Parser)]
+struct RunManifest {
+    /// Transaction manifest file (.rtm format)
+    manifest_path: String,
+    #[arg(long, short, use_value_delimiter = true, value_delimiter = ',')]
+    /// Blob files referenced by the manifest
+    blobs: Vec<String>,
+}
+
+#[derive(Debug, Parser)]
+struct Load {
+    #[arg(long, short = 'a', default_value_t = CRYPTO_SCRYPTO_PACKAGE_ADDRESS.to_string())]
+    /// Package address of the CryptoScrypto blueprint
+    package_address: String,
+    #[arg(long, short = 'c', default_value_t = 10)]
+    /// Number of transactions to submit
+    count: u32,
+    #[arg(long, short = 'j', default_value_t = 4)]
+    /// Number of transactions processed concurrently
+    concurrency: usize,
+    #[command(flatten)]
+    msg: MsgArgs,
+    #[arg(long, short, default_value_t = TEST_PUB_KEY1.to_string())]
+    /// BLS public key to perform verification (hex or base64, compressed or uncompressed)
+    public_key: String,
+    /// BLS signature to verify (hex or base64, compressed or uncompressed)
+    #[arg(long, short, default_value_t = TEST_KCK_MSG1_SIGNATURE.to_string())]
+    signature: String,
+    #[arg(long, default_value_t = Prehash::Keccak256)]
+    /// Hash applied to the message: keccak256, sha256, blake2b or none
+    prehash: Prehash,
+    #[arg(long, default_value_t = 1000)]
+    /// Delay between transaction status polls (milliseconds)
+    poll_interval: u64,
+    #[arg(long, default_value_t = 120)]
+    /// Time after which not finalized transaction is considered timed out (seconds)
+    timeout: u64,
+}
+
+#[derive(Debug, Parser)]
+struct Watch {
+    #[arg(long, short = 'a', default_value_t = CRYPTO_SCRYPTO_PACKAGE_ADDRESS.to_string())]
+    /// Package address of the CryptoScrypto blueprint
+    package_address: String,
+    #[arg(long, short)]
+    /// Ledger state version to start watching from (defaults to the beginning of the ledger)
+    from_state_version: Option<u64>,
+    #[arg(long, short, default_value_t = 100)]
+    /// Number of transactions fetched in a single page
+    limit: u32,
+    #[arg(long, short = 'F')]
+    /// Keep following the stream and wait for new transactions
+    follow: bool,
+    #[arg(long, default_value_t = 5000)]
+    /// Delay between polls for new transactions in follow mode (milliseconds)
+    poll_interval: u64,
+}
+
+#[derive(Debug, Parser)]
+struct TxDetails {
+    /// Intent hash of the transaction (Bech32 format, eg. txid_tdx_21_1...)
+    intent_hash: String,
+    #[arg(long, short)]
+    /// Include events emitted during the transaction execution
+    events: bool,
+    #[arg(long, short)]
+    /// Include substates created, updated and deleted by the transaction
+    state_changes: bool,
+    #[arg(long, short)]
+    /// Include the fee summary
+    fee_summary: bool,
+    #[arg(long, short)]
+    /// Include the raw notarized transaction payload (hex-encoded string)
+    raw_hex: bool,
+    #[arg(long, short)]
+    /// Include the transaction manifest instructions
+    manifest_instructions: bool,
+    #[arg(long, short = 'A')]
+    /// Include all of the above
+    all: bool,
+}
+
+#[derive(Debug, Parser)]
+struct TxStatus {
+    /// Intent hash of the transaction (Bech32 format, eg. txid_tdx_21_1...)
+    intent_hash: String,
+}
+
+#[derive(Debug, Parser)]
+struct TxOutput {
+    /// Intent hash of the transaction (Bech32 format, eg. txid_tdx_21_1...)
+    intent_hash: String,
+    #[arg(long, short = 't')]
+    /// Expected output type: bool, Hash, Bls12381G2Signature or PackageAddress.
+    /// Inferred from the transaction manifest if not given.
+    output_type: Option<OutputType>,
+    #[arg(long, short)]
+    /// Index of the instruction output to decode.
+    /// Inferred from the transaction manifest if not given.
+    index: Option<usize>,
+}
+
+#[derive(Debug, Parser)]
+struct TxDecode {
+    #[arg(required_unless_present = "file")]
+    /// Notarized transaction payload (hex-encoded string)
+    payload: Option<String>,
+    #[arg(long, short, conflicts_with = "payload")]
+    /// File with the notarized transaction payload (hex-encoded string or raw bytes)
+    file: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum ThresholdCommands {
+    /// Split BLS secret key into shares with Shamir's secret sharing
+    Keygen(ThresholdKeygen),
+    /// Sign the message digest (Keccak256 by default) with the key share
+    Sign(ThresholdSign),
+    /// Combine partial signatures into the group signature, optionally verifying it on-ledger
+    Combine(ThresholdCombine),
+}
+
+#[derive(Debug, Parser)]
+struct BlsDerive {
+    #[arg(long)]
+    /// BIP-39 mnemonic (English words separated with spaces)
+    mnemonic: String,
+    #[arg(long, default_value_t = String::new())]
+    /// Optional BIP-39 passphrase
+    passphrase: String,
+    #[arg(long, short, default_value_t = DEFAULT_DERIVATION_PATH.to_string())]
+    /// EIP-2334 derivation path
+    path: String,
+    #[arg(long)]
+    /// Save the derived key into EIP-2335 keystore file instead of printing it
+    keystore_out: Option<String>,
+    #[arg(long, requires = "keystore_out")]
+    /// Password of the new keystore, prompted for if not given
+    new_password: Option<String>,
+}
+
+#[derive(Debug, Args)]
+struct SecretKeyArgs {
+    #[arg(
+        long,
+        short,
+        required_unless_present = "keystore",
+        conflicts_with = "keystore"
+    )]
+    /// BLS secret key (hex-encoded string)
+    secret_key: Option<String>,
+    #[arg(long)]
+    /// EIP-2335 keystore file with the BLS secret key
+    keystore: Option<String>,
+    #[arg(long, requires = "keystore")]
+    /// Keystore password, prompted for if not given
+    password: Option<String>,
+}
+
+impl SecretKeyArgs {
+    // Secret key given directly or decrypted from the keystore
+    fn secret_key(&self) -> BlsSecretKey {
+        match (&self.secret_key, &self.keystore) {
+            (Some(secret_key), _) => BlsSecretKey::from_str(secret_key).unwrap(),
+            (None, Some(file)) => {
+                let keystore = Keystore::load(file).unwrap();
+                let password = self
+                    .password
+                    .clone()
+                    .unwrap_or_else(|| read_password(&format!("Password for {}: ", file)));
+                keystore.decrypt(&password).unwrap()
+            }
+            (None, None) => panic!("Secret key or keystore required"),
+        }
+    }
+}
+
+fn read_password(prompt: &str) -> String {
+    rpassword::prompt_password(prompt).expect("Cannot read password")
+}
+
+// Password of the new keystore, unless given it is prompted for twice
+fn new_keystore_password(password: &Option<String>) -> String {
+    password.clone().unwrap_or_else(|| {
+        let password = read_password("New keystore password: ");
+        let repeated = read_password("Repeat password: ");
+        assert_eq!(password, repeated, "Passwords do not match");
+        password
+    })
+}
+
+#[derive(Debug, Parser)]
+struct BlsSign {
+    #[command(flatten)]
+    key: SecretKeyArgs,
+    #[command(flatten)]
+    msg: MsgArgs,
+    #[arg(long, default_value_t = Prehash::Keccak256)]
+    /// Hash applied to the message: keccak256, sha256, blake2b or none
+    prehash: Prehash,
+}
+
+#[derive(Debug, Parser)]
+struct BlsKeystoreExport {
+    #[command(flatten)]
+    key: SecretKeyArgs,
+    #[arg(long, short)]
+    /// Keystore file to write
+    output: String,
+    #[arg(long, default_value_t = String::new())]
+    /// EIP-2334 derivation path of the key stored in the keystore
+    path: String,
+    #[arg(long)]
+    /// Password of the new keystore, prompted for if not given
+    new_password: Option<String>,
+}
+
+#[derive(Debug, Parser)]
+struct BlsKeystoreImport {
+    /// Keystore file to read
+    keystore: String,
+    #[arg(long)]
+    /// Keystore password, prompted for if not given
+    password: Option<String>,
+    #[arg(long)]
+    /// Print the decrypted secret key as well
+    show_secret: bool,
+}
+
+#[derive(Debug, Parser)]
+struct BlsPopProve {
+    #[command(flatten)]
+    key: SecretKeyArgs,
+}
+
+#[derive(Debug, Parser)]
+struct BlsPopVerify {
+    #[arg(long, short)]
+    /// BLS public key (hex or base64, compressed or uncompressed)
+    public_key: String,
+    #[arg(long)]
+    /// Proof of possession of the public key (hex or base64, compressed or uncompressed)
+    pop: String,
+}
+
+#[derive(Debug, Parser)]
+struct BlsPubkeyAggregate {
+    #[arg(long, short, use_value_delimiter = true, value_delimiter = ',', default_values_t = vec![TEST_PUB_KEY1.to_string(), TEST_PUB_KEY2.to_string()])]
+    /// BLS public keys to aggregate (hex or base64, compressed or uncompressed)
+    public_keys: Vec<String>,
+    #[command(flatten)]
+    msg: MsgArgs,
+    #[arg(long, short)]
+    /// Fast aggregate signature to cross-check with the aggregate key (hex or base64, compressed or uncompressed)
+    signature: Option<String>,
+    #[arg(long, default_value_t = Prehash::None)]
+    /// Hash applied to the message: keccak256, sha256, blake2b or none
+    prehash: Prehash,
+}
+
+#[derive(Debug, Parser)]
+struct BlsConvert {
+    /// Public key (G1) or signature (G2) point, compressed or uncompressed,
+    /// encoded as hex, 0x-prefixed hex or base64
+    point: String,
+}
+
+#[derive(Debug, Parser)]
+struct BlsEdgeCases {
+    #[arg(long, short = 'a', default_value_t = CRYPTO_SCRYPTO_PACKAGE_ADDRESS.to_string())]
+    /// Package address of the CryptoScrypto blueprint
+    package_address: String,
+    #[arg(long, short)]
+    /// Run only the cases whose name contains the given string
+    filter: Option<String>,
+    #[arg(long, short)]
+    /// JSON file to write the outcomes to
+    output: Option<String>,
+}
+
+#[derive(Debug, Parser)]
+struct BlsRogueKeyDemo {
+    #[arg(long, short = 'a', default_value_t = CRYPTO_SCRYPTO_PACKAGE_ADDRESS.to_string())]
+    /// Package address of the CryptoScrypto blueprint
+    package_address: String,
+    #[command(flatten)]
+    msg: MsgArgs,
+    #[arg(long, short, default_value_t = TEST_PUB_KEY1.to_string())]
+    /// Public key of the victim (hex or base64, compressed or uncompressed)
+    victim_public_key: String,
+    #[arg(long, short = 'k', default_value_t = TEST_ATTACKER_SECRET_KEY.to_string())]
+    /// Secret key of the attacker (hex-encoded string)
+    attacker_secret_key: String,
+    #[arg(long, default_value_t = Prehash::None)]
+    /// Hash applied to the message: keccak256, sha256, blake2b or none
+    prehash: Prehash,
+    #[arg(long)]
+    /// Submit the forged signature to the CryptoScrypto fast aggregate verification as well
+    submit: bool,
+}
+
+#[derive(Debug, Parser)]
+struct ThresholdKeygen {
+    #[command(flatten)]
+    key: SecretKeyArgs,
+    #[arg(long, short, default_value_t = 2)]
+    /// Number of shares required to sign
+    threshold: usize,
+    #[arg(long, short = 'n', default_value_t = 3)]
+    /// Number of shares
+    shares: usize,
+    #[arg(long)]
+    /// Save the shares into EIP-2335 keystore files "share-<index>.json" in the directory
+    /// instead of printing them
+    keystore_dir: Option<String>,
+    #[arg(long, requires = "keystore_dir")]
+    /// Password of the share keystores, prompted for if not given
+    new_password: Option<String>,
+}
+
+#[derive(Debug, Parser)]
+struct ThresholdSign {
+    #[arg(
+        long,
+        short = 'k',
+        required_unless_present = "share_keystore",
+        conflicts_with = "share_keystore"
+    )]
+    /// Key share given as "<index>:<secret key hex>"
+    share: Option<String>,
+    #[arg(long)]
+    /// EIP-2335 keystore file with the key share, as written by threshold keygen
+    share_keystore: Option<String>,
+    #[arg(long, requires = "share_keystore")]
+    /// Keystore password, prompted for if not given
+    password: Option<String>,
+    #[command(flatten)]
+    msg: MsgArgs,
+    #[arg(long, default_value_t = Prehash::Keccak256)]
+    /// Hash applied to the message: keccak256, sha256, blake2b or none
+    prehash: Prehash,
+}
+
+#[derive(Debug, Parser)]
+struct ThresholdCombine {
+    #[arg(long, short = 'a', default_value_t = CRYPTO_SCRYPTO_PACKAGE_ADDRESS.to_string())]
+    /// Package address of the CryptoScrypto blueprint
+    package_address: String,
+    #[arg(
+        long,
+        short,
+        required = true,
+        use_value_delimiter = true,
+        value_delimiter = ','
+    )]
+    /// Partial signatures given as "<index>:<signature hex>"
+    partials: Vec<String>,
+    #[command(flatten)]
+    msg: MsgArgs,
+    #[arg(long, short = 'k')]
+    /// Group public key (hex or base64, compressed or uncompressed).
+    /// If given, the combined signature is verified on-ledger with bls-verify.
+    public_key: Option<String>,
+    #[arg(long, default_value_t = Prehash::Keccak256)]
+    /// Hash applied to the message: keccak256, sha256, blake2b or none
+    prehash: Prehash,
+}
+
+// Definition of the network with the given id, among the networks known to the CLI
+fn network_definition_by_id(network_id: u8) -> Option<NetworkDefinition> {
+    match network_id {
+        NETWORK_ID => Some(NetworkDefinition {
+            id: NETWORK_ID,
+            logical_name: String::from(NETWORK_NAME),
+            hrp_suffix: String::from(NETWORK_HRP_SUFFIX),
+        }),
+        MARDUNET_NETWORK_ID => Some(NetworkDefinition {
+            id: MARDUNET_NETWORK_ID,
+            logical_name: String::from(MARDUNET_NETWORK_NAME),
+            hrp_suffix: String::from(MARDUNET_NETWORK_HRP_SUFFIX),
+        }),
+        id if id == NetworkDefinition::mainnet().id => Some(NetworkDefinition::mainnet()),
+        id if id == NetworkDefinition::stokenet().id => Some(NetworkDefinition::stokenet()),
+        _ => None,
+    }
+}
+
+struct NetworkConfig {
+    // None if network shall be detected from the gateway
+    network_definition: Option<NetworkDefinition>,
+    gateway_urls: Vec<String>,
+    // HTTP settings of the gateway client, which can be overridden from the command line
+    gateway_client: GatewayClientConfig,
+}
+
+fn network_config(cli: &Cli) -> NetworkConfig {
+    let gateway_client = GatewayClientConfig {
+        connect_timeout: Some(time::Duration::from_secs(10)),
+        timeout: Some(time::Duration::from_secs(30)),
+        user_agent: Some(format!("bls_tests/{}", env!("CARGO_PKG_VERSION"))),
+        ..Default::default()
+    };
+
+    if let Some(gateway_url) = &cli.gateway_url {
+        return NetworkConfig {
+            network_definition: None,
+            gateway_urls: vec![gateway_url.clone()],
+            gateway_client,
+        };
+    }
+
+    match cli.network.as_str() {
+        MARDUNET_NETWORK_NAME => NetworkConfig {
+            network_definition: Some(NetworkDefinition {
+                id: MARDUNET_NETWORK_ID,
+                logical_name: String::from(MARDUNET_NETWORK_NAME),
+                hrp_suffix: String::from(MARDUNET_NETWORK_HRP_SUFFIX),
+            }),
+            gateway_urls: MARDUNET_GATEWAY_URLS
+                .iter()
+                .map(|u| u.to_string())
+                .collect(),
+            gateway_client,
+        },
+        NETWORK_NAME => NetworkConfig {
+            network_definition: Some(NetworkDefinition {
+                id: NETWORK_ID,
+                logical_name: String::from(NETWORK_NAME),
+                hrp_suffix: String::from(NETWORK_HRP_SUFFIX),
+            }),
+            gateway_urls: GATEWAY_URLS.iter().map(|u| u.to_string()).collect(),
+            gateway_client,
+        },
+        _ => panic!("Network '{}' not supported", cli.network),
+    }
+}
+
+// Infer the output index and type from the raw transaction payload.
+// If the index is given, only the instruction at that index is considered.
+fn infer_output(
+    raw_hex: &str,
+    index: Option<usize>,
+) -> Result<Option<(usize, OutputType)>, String> {
+    let payload =
+        hex::decode(raw_hex).map_err(|err| format!("Invalid raw transaction hex: {}", err))?;
+    let transaction = NotarizedTransactionV1::from_payload_bytes(&payload)
+        .map_err(|err| format!("Invalid transaction payload: {:?}", err))?;
+    let instructions = &transaction.signed_intent.intent.instructions.0;
+    Ok(match index {
+        Some(index) => instructions
+            .get(index..=index)
+            .and_then(OutputType::infer)
+            .map(|(_, output_type)| (index, output_type)),
+        None => OutputType::infer(instructions),
+    })
+}
+
+// Manifest file of the edge case, eg. "cases.rtm" -> "cases.verify_valid.rtm"
+fn case_manifest_path(path: &str, case_name: &str) -> String {
+    let path = std::path::Path::new(path);
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_default();
+    let file_name = match path.extension() {
+        Some(extension) => format!("{}.{}.{}", stem, case_name, extension.to_string_lossy()),
+        None => format!("{}.{}", stem, case_name),
+    };
+    path.with_file_name(file_name).display().to_string()
+}
+
+// Failure of the transaction submission or of waiting for its outcome
+enum SubmitError {
+    Submit(TransactionError),
+    Status(TransactionError),
+}
+
 struct CliCtx {
     gateway: GatewayApiClient,
     network_definition: NetworkDefinition,
@@ -182,28 +1058,63 @@ struct CliCtx {
     address_encoder: AddressBech32Encoder,
     hash_encoder: TransactionHashBech32Encoder,
     private_key: Secp256k1PrivateKey,
+    dry_run: bool,
+    emit_manifest: Option<String>,
 }
 
 impl CliCtx {
-    fn new(network_name: &str) -> Self {
-        let (gateway, network_definition) = match network_name {
-            MARDUNET_NETWORK_NAME => (
-                GatewayApiClient::new(MARDUNET_GATEWAY_URL),
-                NetworkDefinition {
-                    id: MARDUNET_NETWORK_ID,
-                    logical_name: String::from(MARDUNET_NETWORK_NAME),
-                    hrp_suffix: String::from(MARDUNET_NETWORK_HRP_SUFFIX),
-                },
-            ),
-            NETWORK_NAME => (
-                GatewayApiClient::new(GATEWAY_URL),
-                NetworkDefinition {
-                    id: NETWORK_ID,
-                    logical_name: String::from(NETWORK_NAME),
-                    hrp_suffix: String::from(NETWORK_HRP_SUFFIX),
-                },
-            ),
-            _ => panic!("Network '{}' not supported", network_name),
+    fn new(cli: &Cli) -> Self {
+        let network_config = network_config(cli);
+
+        // Command line options take precedence over the network defaults
+        let mut client_config = network_config.gateway_client;
+        if let Some(connect_timeout) = cli.connect_timeout {
+            client_config.connect_timeout = Some(time::Duration::from_millis(connect_timeout));
+        }
+        if let Some(request_timeout) = cli.request_timeout {
+            client_config.timeout = Some(time::Duration::from_millis(request_timeout));
+        }
+        if cli.proxy.is_some() {
+            client_config.proxy = cli.proxy.clone();
+        }
+        if cli.ca_bundle.is_some() {
+            client_config.ca_bundle = cli.ca_bundle.clone();
+        }
+        if cli.user_agent.is_some() {
+            client_config.user_agent = cli.user_agent.clone();
+        }
+        client_config.headers.extend(cli.headers.iter().cloned());
+
+        let gateway = GatewayApiClient::with_urls(&network_config.gateway_urls)
+            .with_client_config(&client_config)
+            .with_retry_config(RetryConfig {
+                max_retries: cli.max_retries,
+                initial_backoff: time::Duration::from_millis(cli.retry_backoff),
+                ..Default::default()
+            })
+            .with_event_hook(|event| println!("{}", event));
+        // Off-ledger commands must work without any gateway reachable
+        let gateway_needed = cli.command.uses_gateway()
+            || matches!(cli.command, Commands::GatewayStatus | Commands::Doctor);
+        // With multiple gateways start with a healthy one
+        if gateway_needed && gateway.urls().len() > 1 && !gateway.select_healthy_gateway() {
+            println!("No healthy gateway found, using {}", gateway.url());
+        }
+        let network_definition = match network_config.network_definition {
+            Some(network_definition) => network_definition,
+            None if gateway_needed => {
+                let network_definition = detect_network_definition(&gateway)
+                    .unwrap_or_else(|err| panic!("Network detection failed: {}", err));
+                println!(
+                    "Detected network: {} (id {:#04x}, HRP suffix '{}')",
+                    network_definition.logical_name,
+                    network_definition.id,
+                    network_definition.hrp_suffix
+                );
+                network_definition
+            }
+            // Network is not detected for off-ledger commands, they encode no addresses
+            None => NetworkDefinition::simulator(),
         };
         let address_decoder = AddressBech32Decoder::new(&network_definition);
         let address_encoder = AddressBech32Encoder::new(&network_definition);
@@ -219,17 +1130,73 @@ impl CliCtx {
             address_encoder,
             hash_encoder,
             private_key,
+            dry_run: cli.dry_run,
+            emit_manifest: cli.emit_manifest.clone(),
         }
     }
 
     fn cmd_gateway_status(&self) {
-        let status = self.gateway.gateway_status();
+        let status = self
+            .gateway
+            .gateway_status()
+            .unwrap_or_else(|err| panic!("gateway request error: {}", err.message));
         println!("gw status = {:?}", status);
     }
 
-    fn execute_transaction(&self, manifest: TransactionManifestV1) -> TransactionDetails {
+    fn cmd_doctor(&self) {
+        println!("Network         : {}", self.network_definition.logical_name);
+        println!("Gateway         : {}", self.gateway.url());
+
+        let results = check_compatibility(&self.gateway, &self.network_definition);
+        for result in &results {
+            println!("{}", result);
+        }
+        if results.iter().any(|r| !r.passed()) {
+            panic!("Gateway compatibility check failed");
+        }
+    }
+
+    // Verify gateway compatibility before running the command, fail listing the failed checks
+    fn check_gateway(&self) {
+        let failed: Vec<String> = check_compatibility(&self.gateway, &self.network_definition)
+            .iter()
+            .filter(|r| !r.passed())
+            .map(|r| r.to_string())
+            .collect();
+        if !failed.is_empty() {
+            panic!(
+                "Gateway {} is not compatible (use --skip-checks to ignore):\n{}",
+                self.gateway.url(),
+                failed.join("\n")
+            );
+        }
+    }
+
+    // Sign and submit the transaction and wait until it is committed.
+    // Returns None in dry-run mode, when the manifest is only emitted.
+    fn execute_transaction(&self, manifest: TransactionManifestV1) -> Option<TransactionDetails> {
         let current_epoch = self.gateway.current_epoch();
 
+        if self.dry_run {
+            self.emit_dry_run_manifest(&manifest, current_epoch);
+            return None;
+        }
+
+        let (_, result) = self.submit_transaction(manifest, current_epoch);
+        match result {
+            Ok(details) => Some(details),
+            Err(SubmitError::Submit(err)) => panic!("transaction submit error: {:?}", err),
+            Err(SubmitError::Status(err)) => panic!("transaction status error: {:?}", err),
+        }
+    }
+
+    // Sign and submit the transaction and wait until it is committed.
+    // Returns the intent hash along with the transaction details or the failure.
+    fn submit_transaction(
+        &self,
+        manifest: TransactionManifestV1,
+        current_epoch: u64,
+    ) -> (String, Result<TransactionDetails, SubmitError>) {
         let (notarized_transaction, intent_hash) = create_notarized_transaction(
             &self.network_definition,
             current_epoch,
@@ -246,86 +1213,122 @@ impl CliCtx {
         println!("intent_hash : {}", intent_hash);
 
         if let Err(err) = self.gateway.transaction_submit(notarized_transaction) {
-            panic!("transaction submit error: {:?}", err);
+            return (intent_hash, Err(SubmitError::Submit(err)));
         }
 
         // Wait for transaction finish
-        loop {
-            let status = self.gateway.transaction_status(&intent_hash);
-            match status {
-                Ok(status) => {
-                    if !status.status.eq("Pending") {
-                        break;
-                    }
+        let result = self
+            .gateway
+            .wait_for_transaction(&intent_hash)
+            .map_err(SubmitError::Status);
+        (intent_hash, result)
+    }
+
+    // Client of the CryptoScrypto package given in bech32 format
+    fn crypto_scrypto_client(&self, package_address: &str) -> CryptoScryptoClient {
+        let package_address =
+            PackageAddress::try_from_bech32(&self.address_decoder, package_address)
+                .expect("Invalid package address");
+        CryptoScryptoClient::new(
+            self.gateway.clone(),
+            self.network_definition.clone(),
+            Secp256k1PrivateKey::from_bytes(&self.private_key.to_bytes()).unwrap(),
+            package_address,
+        )
+    }
+
+    // Call the CryptoScrypto function with the manifest built by the client
+    // and decode its output. Returns None in dry-run mode.
+    fn call_crypto_scrypto<I: CryptoScryptoFunctionInput>(
+        &self,
+        client: &CryptoScryptoClient,
+        input: &I,
+    ) -> Option<I::Output> {
+        let details = self.execute_transaction(client.manifest(input))?;
+        Some(decode_output(&details, 1).unwrap_or_else(|err| panic!("Transaction error: {}", err)))
+    }
+
+    // Print the manifest in .rtm format or write it to the file given with --emit-manifest
+    fn emit_dry_run_manifest(&self, manifest: &TransactionManifestV1, current_epoch: u64) {
+        self.emit_dry_run_manifest_to(manifest, current_epoch, self.emit_manifest.as_deref());
+    }
+
+    // Like emit_dry_run_manifest, but the manifest is written to the given file (if any)
+    fn emit_dry_run_manifest_to(
+        &self,
+        manifest: &TransactionManifestV1,
+        current_epoch: u64,
+        emit_manifest: Option<&str>,
+    ) {
+        let manifest_text = decompile_manifest(&self.network_definition, &manifest.instructions);
+
+        println!("Dry run, transaction not submitted");
+        println!("Start epoch     : {} (estimated)", current_epoch);
+        println!(
+            "End epoch       : {} (estimated, exclusive)",
+            current_epoch + EPOCH_VALIDITY_RANGE
+        );
+
+        match emit_manifest {
+            Some(path) => {
+                fs::write(path, manifest_text).unwrap();
+                println!("Manifest file   : {}", path);
+
+                let dir = std::path::Path::new(path)
+                    .parent()
+                    .unwrap_or(std::path::Path::new(""));
+                for (hash, blob) in &manifest.blobs {
+                    let blob_path = dir.join(format!("{}.blob", hash));
+                    fs::write(&blob_path, blob).unwrap();
+                    println!("Blob file       : {}", blob_path.display());
                 }
-                Err(err) => panic!("transaction status error: {:?}", err),
             }
-            thread::sleep(time::Duration::from_millis(1000));
-        }
-
-        match self.gateway.transaction_details(&intent_hash) {
-            Ok(details) => details,
-            Err(err) => panic!("transaction submit error: {:?}", err),
+            None => {
+                println!("Manifest:");
+                println!("{}", manifest_text);
+            }
         }
     }
 
     // Call CryptoScrypto package "keccak256_hash" method to retrieve the digest of the message.
     fn cmd_keccak_hash(&self, cmd: &KeccakHash) {
-        // Convert address from the human-readable bech32 format
-        let package_address =
-            PackageAddress::try_from_bech32(&self.address_decoder, &cmd.package_address)
-                .expect("Invalid package address");
-        let data = cmd.msg.as_bytes().to_vec();
+        let client = self.crypto_scrypto_client(&cmd.package_address);
 
-        println!("Package address : {}", cmd.package_address);
-        println!("Message         : {}", cmd.msg);
+        let msg = cmd.msg.bytes();
 
-        // Build manifest
-        let manifest = ManifestBuilder::new()
-            .lock_fee_from_faucet()
-            .call_function(
-                package_address,
-                CRYPTO_SCRYPTO_BLUEPRINT_NAME,
-                "keccak256_hash",
-                manifest_args!(&data),
-            )
-            .build();
+        println!("Package address : {}", cmd.package_address);
+        println!("Message         : {}", format_bytes(&msg));
 
-        let details = self.execute_transaction(manifest);
-        let value: Hash = scrypto_decode(&transaction_output(details)).unwrap();
+        let input = Keccak256HashInput { data: msg };
+        let Some(value) = self.call_crypto_scrypto(&client, &input) else {
+            return;
+        };
         println!("Message hash    : {}", value);
     }
 
     // Call CryptoScrypto package "bls12381_v1_verify" method to verify the signature
     fn cmd_bls_verify(&self, cmd: &BlsVerify) {
-        // Convert address from the human-readable bech32 format
-        let package_address =
-            PackageAddress::try_from_bech32(&self.address_decoder, &cmd.package_address)
-                .expect("Invalid package address");
-        let msg_hash = keccak256_hash(cmd.msg.clone());
+        let client = self.crypto_scrypto_client(&cmd.package_address);
+        let msg = cmd.msg.bytes();
+        let digest = cmd.prehash.digest(&msg);
 
         println!("Package address : {}", cmd.package_address);
-        println!("Message         : {}", cmd.msg);
-        println!("Message hash    : {}", msg_hash);
+        println!("Message         : {}", format_bytes(&msg));
+        print_digest(cmd.prehash, &digest);
         println!("Publick key     : {}", cmd.public_key);
         println!("Signature       : {}", cmd.signature);
 
-        let pub_key = Bls12381G1PublicKey::from_str(&cmd.public_key).unwrap();
-        let signature = Bls12381G2Signature::from_str(&cmd.signature).unwrap();
-
-        // Build manifest
-        let manifest = ManifestBuilder::new()
-            .lock_fee_from_faucet()
-            .call_function(
-                package_address,
-                CRYPTO_SCRYPTO_BLUEPRINT_NAME,
-                "bls12381_v1_verify",
-                manifest_args!(msg_hash.to_vec(), pub_key, signature),
-            )
-            .build();
+        let pub_key = bls::parse_public_key(&cmd.public_key).unwrap();
+        let signature = bls::parse_signature(&cmd.signature).unwrap();
 
-        let details = self.execute_transaction(manifest);
-        let value: bool = scrypto_decode(&transaction_output(details)).unwrap();
+        let input = Bls12381V1VerifyInput {
+            message: digest,
+            pub_key,
+            signature,
+        };
+        let Some(value) = self.call_crypto_scrypto(&client, &input) else {
+            return;
+        };
         println!("BLS verify  : {:?}", value);
     }
 
@@ -350,7 +1353,9 @@ impl CliCtx {
             .publish_package_advanced(None, code, rpd, metadata, OwnerRole::None)
             .build();
 
-        let details = self.execute_transaction(manifest);
+        let Some(details) = self.execute_transaction(manifest) else {
+            return;
+        };
         let value: PackageAddress = scrypto_decode(&transaction_output(details)).unwrap();
         // Encode the address into human-readabl bech32 format
         let address = self.address_encoder.encode(value.as_ref()).unwrap();
@@ -372,88 +1377,88 @@ impl CliCtx {
             .try_deposit_entire_worktop_or_abort(account_address, None)
             .build();
 
-        let details = self.execute_transaction(manifest);
+        let Some(details) = self.execute_transaction(manifest) else {
+            return;
+        };
         let _ = transaction_output(details);
         println!("XRD freed to address: {:?}", cmd.account_address);
     }
 
     fn cmd_bls_aggregate_verify(&self, cmd: &BlsAggregateVerify) {
-        // Convert address from the human-readable bech32 format
-        let package_address =
-            PackageAddress::try_from_bech32(&self.address_decoder, &cmd.package_address)
-                .expect("Invalid package address");
+        let client = self.crypto_scrypto_client(&cmd.package_address);
+
+        let msgs = cmd.msgs.bytes();
+        let msgs_desc: Vec<String> = msgs.iter().map(|msg| format_bytes(msg)).collect();
 
         println!("Package address : {}", cmd.package_address);
-        println!("Messages        : {:?}", cmd.msgs);
+        println!("Messages        : [{}]", msgs_desc.join(", "));
         println!("Public  keys    : {:?}", cmd.public_keys);
         println!("Signature       : {:?}", cmd.signature);
 
+        // Zip would silently drop the extra messages or public keys
+        assert_eq!(
+            msgs.len(),
+            cmd.public_keys.len(),
+            "Expected the same number of messages and public keys"
+        );
         let pub_keys_msgs: Vec<(Bls12381G1PublicKey, Vec<u8>)> = cmd
             .public_keys
             .iter()
-            .zip(cmd.msgs.clone())
-            .map(|(pk, msg)| (Bls12381G1PublicKey::from_str(pk).unwrap(), msg.into_bytes()))
+            .zip(&msgs)
+            .map(|(pk, msg)| (bls::parse_public_key(pk).unwrap(), cmd.prehash.digest(msg)))
             .collect();
+        for (_, digest) in &pub_keys_msgs {
+            print_digest(cmd.prehash, digest);
+        }
 
-        let signature = Bls12381G2Signature::from_str(&cmd.signature).unwrap();
+        let signature = bls::parse_signature(&cmd.signature).unwrap();
 
-        // Build manifest
-        let manifest = ManifestBuilder::new()
-            .lock_fee_from_faucet()
-            .call_function(
-                package_address,
-                CRYPTO_SCRYPTO_BLUEPRINT_NAME,
-                "bls12381_v1_aggregate_verify",
-                manifest_args!(pub_keys_msgs, signature),
-            )
-            .build();
-
-        let details = self.execute_transaction(manifest);
-        let value: bool = scrypto_decode(&transaction_output(details)).unwrap();
+        let input = Bls12381V1AggregateVerifyInput {
+            pub_keys_msgs,
+            signature,
+        };
+        let Some(value) = self.call_crypto_scrypto(&client, &input) else {
+            return;
+        };
         println!("BLS aggregate verify  : {:?}", value);
     }
 
     fn cmd_bls_fast_aggregate_verify(&self, cmd: &BlsFastAggregateVerify) {
-        // Convert address from the human-readable bech32 format
-        let package_address =
-            PackageAddress::try_from_bech32(&self.address_decoder, &cmd.package_address)
-                .expect("Invalid package address");
+        let client = self.crypto_scrypto_client(&cmd.package_address);
 
         println!("Package address : {}", cmd.package_address);
-        println!("Message         : {:?}", cmd.msg);
+        let msg = cmd.msg.bytes();
+        println!("Message         : {}", format_bytes(&msg));
         println!("Public keys     : {:?}", cmd.public_keys);
         println!("Signature       : {:?}", cmd.signature);
 
-        let msg = cmd.msg.clone().into_bytes();
+        let msg = cmd.prehash.digest(&msg);
+        print_digest(cmd.prehash, &msg);
         let pub_keys: Vec<Bls12381G1PublicKey> = cmd
             .public_keys
             .iter()
-            .map(|pk| Bls12381G1PublicKey::from_str(pk).unwrap())
+            .map(|pk| bls::parse_public_key(pk).unwrap())
             .collect();
 
-        let signature = Bls12381G2Signature::from_str(&cmd.signature).unwrap();
+        let signature = bls::parse_signature(&cmd.signature).unwrap();
 
-        // Build manifest
-        let manifest = ManifestBuilder::new()
-            .lock_fee_from_faucet()
-            .call_function(
-                package_address,
-                CRYPTO_SCRYPTO_BLUEPRINT_NAME,
-                "bls12381_v1_fast_aggregate_verify",
-                manifest_args!(msg, pub_keys, signature),
-            )
-            .build();
+        if cmd.require_pop {
+            check_pops(&pub_keys, &cmd.pops);
+        }
 
-        let details = self.execute_transaction(manifest);
-        let value: bool = scrypto_decode(&transaction_output(details)).unwrap();
+        let input = Bls12381V1FastAggregateVerifyInput {
+            message: msg,
+            pub_keys,
+            signature,
+        };
+        let Some(value) = self.call_crypto_scrypto(&client, &input) else {
+            return;
+        };
         println!("BLS fast aggregate verify  : {:?}", value);
     }
 
     fn cmd_bls_signature_aggregate(&self, cmd: &BlsSignatureAggregate) {
-        // Convert address from the human-readable bech32 format
-        let package_address =
-            PackageAddress::try_from_bech32(&self.address_decoder, &cmd.package_address)
-                .expect("Invalid package address");
+        let client = self.crypto_scrypto_client(&cmd.package_address);
 
         println!("Package address : {}", cmd.package_address);
         println!("Signatures      : {:?}", cmd.signatures);
@@ -461,34 +1466,1021 @@ impl CliCtx {
         let signatures: Vec<Bls12381G2Signature> = cmd
             .signatures
             .iter()
-            .map(|s| Bls12381G2Signature::from_str(s).unwrap())
+            .map(|s| bls::parse_signature(s).unwrap())
             .collect();
 
+        let input = Bls12381G2SignatureAggregateInput { signatures };
+        let Some(value) = self.call_crypto_scrypto(&client, &input) else {
+            return;
+        };
+        println!("BLS signature aggregate  : {:?}", value);
+    }
+
+    // Call multiple CryptoScrypto functions in a single transaction
+    fn cmd_batch(&self, cmd: &Batch) {
+        // Convert address from the human-readable bech32 format
+        let package_address =
+            PackageAddress::try_from_bech32(&self.address_decoder, &cmd.package_address)
+                .expect("Invalid package address");
+        let calls: Vec<BatchCall> =
+            serde_json::from_str(&fs::read_to_string(&cmd.calls_path).unwrap())
+                .expect("Invalid batch file");
+
+        println!("Package address : {}", cmd.package_address);
+        println!("Calls           : {}", calls.len());
+
+        // Build manifest
+        let mut builder = ManifestBuilder::new().lock_fee_from_faucet();
+        // Output index of each call, the first instruction locks the fee
+        let mut output_indices = vec![];
+        for (idx, call) in calls.iter().enumerate() {
+            builder = call.add_to_manifest(builder, package_address);
+            output_indices.push(idx + 1);
+        }
+        let manifest = builder.build();
+
+        let Some(details) = self.execute_transaction(manifest) else {
+            return;
+        };
+        if let Some(error) = details.get_error() {
+            panic!("Transaction error: {:?}", error);
+        }
+
+        for (idx, (call, output_idx)) in calls.iter().zip(output_indices).enumerate() {
+            let function = call.function();
+            let value = details
+                .get_output(output_idx)
+                .and_then(|output| {
+                    function
+                        .output_type()
+                        .describe(&hex::decode(output).unwrap(), &self.address_encoder)
+                })
+                .unwrap_or_else(|| "<missing output>".to_string());
+            println!("Call [{}] {} : {}", idx, function, value);
+        }
+    }
+
+    // Compile and run the manifest from the file.
+    // Fee is locked from the faucet, unless manifest locks it by itself.
+    fn cmd_run_manifest(&self, cmd: &RunManifest) {
+        println!("Manifest file   : {}", cmd.manifest_path);
+        println!("Blob files      : {:?}", cmd.blobs);
+
+        let manifest_text = fs::read_to_string(&cmd.manifest_path).unwrap();
+        let blobs: Vec<Vec<u8>> = cmd.blobs.iter().map(|b| fs::read(b).unwrap()).collect();
+
+        let manifest = with_fee_lock(compile_manifest(
+            &self.network_definition,
+            &manifest_text,
+            blobs,
+        ));
+        let instructions = manifest.instructions.clone();
+
+        let Some(details) = self.execute_transaction(manifest) else {
+            return;
+        };
+        if let Some(error) = details.get_error() {
+            panic!("Transaction error: {:?}", error);
+        }
+
+        // Print output of every instruction.
+        // Outputs of the known CryptoScrypto functions are decoded.
+        for (idx, instruction) in instructions.iter().enumerate() {
+            let Some(output) = details.get_output(idx) else {
+                continue;
+            };
+            let decoded = OutputType::infer(std::slice::from_ref(instruction)).and_then(
+                |(_, output_type)| {
+                    output_type.describe(&hex::decode(&output).unwrap(), &self.address_encoder)
+                },
+            );
+            match decoded {
+                Some(value) => println!("Output [{}]      : {}", idx, value),
+                None => println!("Output [{}]      : {}", idx, output),
+            }
+        }
+    }
+
+    // Submit many BLS verification transactions concurrently.
+    // Each transaction gets its own nonce, so that all intent hashes are unique.
+    fn cmd_load(&self, cmd: &Load) {
+        // Convert address from the human-readable bech32 format
+        let package_address =
+            PackageAddress::try_from_bech32(&self.address_decoder, &cmd.package_address)
+                .expect("Invalid package address");
+        let digest = cmd.prehash.digest(&cmd.msg.bytes());
+        let pub_key = bls::parse_public_key(&cmd.public_key).unwrap();
+        let signature = bls::parse_signature(&cmd.signature).unwrap();
+
+        println!("Package address : {}", cmd.package_address);
+        println!("Transactions    : {}", cmd.count);
+        println!("Concurrency     : {}", cmd.concurrency);
+        print_digest(cmd.prehash, &digest);
+
         // Build manifest
         let manifest = ManifestBuilder::new()
             .lock_fee_from_faucet()
-            .call_function(
-                package_address,
-                CRYPTO_SCRYPTO_BLUEPRINT_NAME,
-                "bls12381_g2_signature_aggregate",
-                manifest_args!(signatures),
-            )
+            .call_bls12381_v1_verify(package_address, &digest, &pub_key, &signature)
             .build();
 
-        let details = self.execute_transaction(manifest);
-        let value: Bls12381G2Signature = scrypto_decode(&transaction_output(details)).unwrap();
-        println!("BLS signature aggregate  : {:?}", value);
+        if self.dry_run {
+            self.emit_dry_run_manifest(&manifest, self.gateway.current_epoch());
+            return;
+        }
+
+        // Random nonces, so that concurrent or subsequent runs do not produce
+        // the same intent hashes. Within the run nonces are kept unique.
+        let mut used_nonces = std::collections::HashSet::new();
+        let current_epoch = self.gateway.current_epoch();
+        let transactions: Vec<(NotarizedTransactionV1, String)> = (0..cmd.count)
+            .map(|_| {
+                let nonce = loop {
+                    let nonce = rand::random::<u32>();
+                    if used_nonces.insert(nonce) {
+                        break nonce;
+                    }
+                };
+                let (transaction, intent_hash) = create_notarized_transaction_with_nonce(
+                    &self.network_definition,
+                    current_epoch,
+                    &self.private_key,
+                    manifest.clone(),
+                    nonce,
+                );
+                (transaction, self.hash_encoder.encode(&intent_hash).unwrap())
+            })
+            .collect();
+
+        let report = run_load(
+            &self.gateway,
+            transactions,
+            cmd.concurrency,
+            time::Duration::from_millis(cmd.poll_interval),
+            time::Duration::from_secs(cmd.timeout),
+        );
+
+        println!("Elapsed         : {:.2} s", report.elapsed.as_secs_f64());
+        println!(
+            "Succeeded       : {}",
+            report.count(|s| *s == LoadStatus::CommittedSuccess)
+        );
+        println!(
+            "Failed          : {}",
+            report.count(|s| *s == LoadStatus::CommittedFailure)
+        );
+        println!(
+            "Rejected        : {}",
+            report.count(|s| matches!(s, LoadStatus::Rejected(_)))
+        );
+        println!(
+            "Status errors   : {}",
+            report.count(|s| matches!(s, LoadStatus::StatusError(_)))
+        );
+        println!(
+            "Duplicates      : {}",
+            report.count(|s| *s == LoadStatus::Duplicate)
+        );
+        println!(
+            "Timed out       : {}",
+            report.count(|s| *s == LoadStatus::TimedOut)
+        );
+        println!("Throughput      : {:.2} tx/s", report.throughput());
+        for percentile in [50.0, 90.0, 99.0, 100.0] {
+            if let Some(latency) = report.latency_percentile(percentile) {
+                println!("Latency p{:<3}   : {} ms", percentile, latency.as_millis());
+            }
+        }
+        for result in &report.results {
+            match &result.final_status {
+                LoadStatus::Rejected(reason) => {
+                    println!("Rejected {} : {}", result.intent_hash, reason)
+                }
+                LoadStatus::StatusError(error) => {
+                    println!("Status error {} : {}", result.intent_hash, error)
+                }
+                _ => {}
+            }
+        }
     }
+
+    // Stream committed transactions, which affected the CryptoScrypto package
+    // and print decoded CryptoScrypto calls along with their outputs.
+    fn cmd_watch(&self, cmd: &Watch) {
+        // Convert address from the human-readable bech32 format
+        let package_address =
+            PackageAddress::try_from_bech32(&self.address_decoder, &cmd.package_address)
+                .expect("Invalid package address");
+
+        println!("Package address : {}", cmd.package_address);
+
+        let mut request = StreamTransactionsRequest {
+            from_ledger_state: cmd
+                .from_state_version
+                .map(|state_version| LedgerStateSelector { state_version }),
+            limit_per_page: Some(cmd.limit),
+            kind_filter: Some("User".to_string()),
+            affected_global_entities_filter: vec![cmd.package_address.clone()],
+            order: Some("Asc".to_string()),
+            // Raw transaction payload is needed to decode the manifest
+            opt_ins: TransactionDetailsOptIns {
+                raw_hex: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut last_state_version = None;
+
+        loop {
+            let page = match self.gateway.stream_transactions(&request) {
+                Ok(page) => page,
+                Err(err) => panic!("transaction stream error: {:?}", err),
+            };
+
+            for item in &page.items {
+                self.print_committed_transaction(&package_address, item);
+                last_state_version = Some(item.state_version);
+            }
+
+            match page.next_cursor {
+                Some(cursor) => request.cursor = Some(cursor),
+                None if cmd.follow => {
+                    // Cursor is bound to the ledger state of the first page,
+                    // so start a new stream right after the last seen transaction.
+                    request.cursor = None;
+                    if let Some(state_version) = last_state_version {
+                        request.from_ledger_state = Some(LedgerStateSelector {
+                            state_version: state_version + 1,
+                        });
+                    }
+                    thread::sleep(time::Duration::from_millis(cmd.poll_interval));
+                }
+                None => break,
+            }
+        }
+    }
+
+    fn print_committed_transaction(
+        &self,
+        package_address: &PackageAddress,
+        item: &CommittedTransactionInfo,
+    ) {
+        println!("----------------");
+        println!("State version   : {}", item.state_version);
+        println!(
+            "Intent hash     : {}",
+            item.intent_hash.as_deref().unwrap_or("-")
+        );
+        println!(
+            "Confirmed at    : {}",
+            item.confirmed_at.as_deref().unwrap_or("-")
+        );
+        println!("Status          : {}", item.transaction_status);
+        println!(
+            "Fee paid        : {}",
+            item.fee_paid.as_deref().unwrap_or("-")
+        );
+        if let Some(error) = &item.error_message {
+            println!("Error           : {}", error);
+        }
+
+        let Some(raw_hex) = &item.raw_hex else {
+            return;
+        };
+        let transaction =
+            NotarizedTransactionV1::from_payload_bytes(&hex::decode(raw_hex).unwrap())
+                .expect("Invalid transaction payload");
+
+        let calls = CryptoScryptoCall::find_all(
+            package_address,
+            &transaction.signed_intent.intent.instructions.0,
+        );
+        for call in calls {
+            println!(
+                "Call [{}]        : {}",
+                call.instruction_index, call.function
+            );
+            println!(
+                "  Arguments     : {}",
+                call.function
+                    .describe_args(&call.args)
+                    .unwrap_or_else(|| "<unexpected arguments>".to_string())
+            );
+            if let Some(output) = item.get_output(call.instruction_index) {
+                println!(
+                    "  Output        : {}",
+                    call.function
+                        .output_type()
+                        .describe(&hex::decode(output).unwrap(), &self.address_encoder)
+                        .unwrap_or_else(|| "<unexpected output>".to_string())
+                );
+            }
+        }
+    }
+
+    // Get details of the committed transaction and pretty-print requested receipt parts
+    fn cmd_tx_details(&self, cmd: &TxDetails) {
+        let opt_ins = TransactionDetailsOptIns {
+            raw_hex: cmd.raw_hex || cmd.all,
+            receipt_output: true,
+            receipt_events: cmd.events || cmd.all,
+            receipt_state_changes: cmd.state_changes || cmd.all,
+            receipt_fee_summary: cmd.fee_summary || cmd.all,
+            manifest_instructions: cmd.manifest_instructions || cmd.all,
+        };
+
+        let details = match self
+            .gateway
+            .transaction_details_with_opt_ins(&cmd.intent_hash, opt_ins)
+        {
+            Ok(details) => details,
+            Err(err) => panic!("transaction details error: {:?}", err),
+        };
+        let transaction = &details.transaction;
+        let receipt = &transaction.receipt;
+
+        println!("Intent hash     : {}", transaction.intent_hash);
+        println!("Payload hash    : {}", transaction.payload_hash);
+        println!("Status          : {}", transaction.transaction_status);
+        println!("State version   : {}", transaction.state_version);
+        println!("Epoch           : {}", transaction.epoch);
+        println!("Round           : {}", transaction.round);
+        println!("Confirmed at    : {}", transaction.confirmed_at);
+        println!("Fee paid        : {}", transaction.fee_paid);
+        if let Some(error) = &receipt.error_message {
+            println!("Error           : {}", error);
+        }
+
+        if let Some(outputs) = &receipt.output {
+            println!("Outputs:");
+            for (idx, output) in outputs.iter().enumerate() {
+                println!("  [{}] {}", idx, output.hex);
+            }
+        }
+
+        if let Some(fee_summary) = &receipt.fee_summary {
+            println!("Fee summary:");
+            println!(
+                "  Execution cost units    : {}",
+                fee_summary.execution_cost_units_consumed
+            );
+            println!(
+                "  Finalization cost units : {}",
+                fee_summary.finalization_cost_units_consumed
+            );
+            println!(
+                "  Execution cost (XRD)    : {}",
+                fee_summary.xrd_total_execution_cost
+            );
+            println!(
+                "  Finalization cost (XRD) : {}",
+                fee_summary.xrd_total_finalization_cost
+            );
+            println!(
+                "  Royalty cost (XRD)      : {}",
+                fee_summary.xrd_total_royalty_cost
+            );
+            println!(
+                "  Storage cost (XRD)      : {}",
+                fee_summary.xrd_total_storage_cost
+            );
+            println!(
+                "  Tipping cost (XRD)      : {}",
+                fee_summary.xrd_total_tipping_cost
+            );
+        }
+
+        if let Some(events) = &receipt.events {
+            println!("Events:");
+            for event in events {
+                let emitter = match &event.emitter {
+                    EventEmitter::Method {
+                        entity,
+                        object_module_id,
+                    } => format!("{} ({})", entity.entity_address, object_module_id),
+                    EventEmitter::Function {
+                        package_address,
+                        blueprint_name,
+                    } => format!("{}::{}", package_address, blueprint_name),
+                };
+                println!("  {} emitted by {}", event.name, emitter);
+                println!(
+                    "    {}",
+                    serde_json::to_string_pretty(&event.data)
+                        .unwrap()
+                        .replace('\n', "\n    ")
+                );
+            }
+        }
+
+        if let Some(state_updates) = &receipt.state_updates {
+            println!("State changes:");
+            for entity in &state_updates.new_global_entities {
+                println!(
+                    "  New entity      : {} ({})",
+                    entity.entity_address, entity.entity_type
+                );
+            }
+            for substate in &state_updates.created_substates {
+                println!(
+                    "  Created         : {}",
+                    format_substate_id(&substate.substate_id)
+                );
+            }
+            for substate in &state_updates.updated_substates {
+                println!(
+                    "  Updated         : {}",
+                    format_substate_id(&substate.substate_id)
+                );
+            }
+            for substate in &state_updates.deleted_substates {
+                println!(
+                    "  Deleted         : {}",
+                    format_substate_id(&substate.substate_id)
+                );
+            }
+        }
+
+        if let Some(manifest) = &transaction.manifest_instructions {
+            println!("Manifest:");
+            println!("{}", manifest);
+        }
+
+        if let Some(raw_hex) = &transaction.raw_hex {
+            println!("Raw transaction : {}", raw_hex);
+        }
+    }
+
+    fn cmd_tx_status(&self, cmd: &TxStatus) {
+        let status = match self.gateway.transaction_status(&cmd.intent_hash) {
+            Ok(status) => status,
+            Err(err) => panic!("transaction status error: {:?}", err),
+        };
+
+        println!("Intent hash     : {}", cmd.intent_hash);
+        println!("Status          : {}", status.status);
+        println!("Intent status   : {}", status.intent_status);
+        println!("Description     : {}", status.intent_status_description);
+        if let Some(state_version) = status.committed_state_version {
+            println!("State version   : {}", state_version);
+        }
+        if let Some(error) = &status.error_message {
+            println!("Error           : {}", error);
+        }
+        for payload in &status.known_payloads {
+            println!("Payload         : {}", payload.payload_hash);
+            println!("  Status        : {}", payload.payload_status);
+            println!("  Description   : {}", payload.payload_status_description);
+            println!("  Handling      : {}", payload.handling_status);
+            println!("  Reason        : {}", payload.handling_status_reason);
+        }
+    }
+
+    // Decode the output of already committed transaction.
+    // If the type of the output is not given, then it is inferred from the manifest
+    // (raw transaction payload is decoded to get the called function).
+    fn cmd_tx_output(&self, cmd: &TxOutput) {
+        let opt_ins = TransactionDetailsOptIns {
+            raw_hex: cmd.output_type.is_none() || cmd.index.is_none(),
+            ..Default::default()
+        };
+        let details = match self
+            .gateway
+            .transaction_details_with_opt_ins(&cmd.intent_hash, opt_ins)
+        {
+            Ok(details) => details,
+            Err(err) => panic!("transaction details error: {:?}", err),
+        };
+
+        let inferred = match details
+            .transaction
+            .raw_hex
+            .as_ref()
+            .map(|raw_hex| infer_output(raw_hex, cmd.index))
+            .transpose()
+        {
+            Ok(inferred) => inferred.flatten(),
+            Err(err) => {
+                println!("Error           : {}", err);
+                return;
+            }
+        };
+
+        let (index, output_type) = match (cmd.index, cmd.output_type, inferred) {
+            (Some(index), Some(output_type), _) => (index, output_type),
+            (index, output_type, Some((inferred_index, inferred_type))) => (
+                index.unwrap_or(inferred_index),
+                output_type.unwrap_or(inferred_type),
+            ),
+            // Manifests built by this tool return the output in the second instruction
+            (index, Some(output_type), None) => (index.unwrap_or(1), output_type),
+            (_, None, None) => {
+                panic!("Cannot infer output type from the manifest, use --output-type")
+            }
+        };
+
+        println!("Intent hash     : {}", cmd.intent_hash);
+        println!(
+            "Status          : {}",
+            details.transaction.transaction_status
+        );
+        println!("Output index    : {}", index);
+        println!("Output type     : {:?}", output_type);
+
+        match details.get_output(index) {
+            Some(output) => {
+                let value = output_type
+                    .describe(&hex::decode(output).unwrap(), &self.address_encoder)
+                    .expect("Output does not match the expected type");
+                println!("Output          : {}", value);
+            }
+            None => match details.get_error() {
+                Some(error) => println!("Error           : {}", error),
+                None => println!("No output at index {}", index),
+            },
+        }
+    }
+
+    // Validate the notarized transaction payload and print its hashes, header and manifest
+    fn cmd_tx_decode(&self, cmd: &TxDecode) {
+        let payload = match (&cmd.payload, &cmd.file) {
+            (Some(payload), _) => hex::decode(payload.trim()).expect("Invalid hex payload"),
+            (None, Some(file)) => {
+                let content = fs::read(file).unwrap();
+                // File may contain either hex-encoded or raw payload
+                match std::str::from_utf8(&content)
+                    .ok()
+                    .and_then(|s| hex::decode(s.trim()).ok())
+                {
+                    Some(payload) => payload,
+                    None => content,
+                }
+            }
+            (None, None) => unreachable!(),
+        };
+
+        let transaction =
+            NotarizedTransactionV1::from_payload_bytes(&payload).expect("Invalid payload");
+        let prepared = transaction
+            .prepare()
+            .expect("Transaction preparation failed");
+        let intent = &transaction.signed_intent.intent;
+        let header = &intent.header;
+
+        // Hashes and addresses are encoded for the network of the transaction,
+        // which may differ from the network the CLI is configured for
+        let network_definition = if header.network_id == self.network_definition.id {
+            self.network_definition.clone()
+        } else {
+            network_definition_by_id(header.network_id).unwrap_or_else(|| {
+                panic!(
+                    "Transaction is for unknown network {:#04x}, cannot decode its addresses",
+                    header.network_id
+                )
+            })
+        };
+        let hash_encoder = TransactionHashBech32Encoder::new(&network_definition);
+
+        println!(
+            "Intent hash        : {}",
+            hash_encoder.encode(&prepared.intent_hash()).unwrap()
+        );
+        println!(
+            "Signed intent hash : {}",
+            hash_encoder.encode(&prepared.signed_intent_hash()).unwrap()
+        );
+        println!(
+            "Network            : {} ({:#04x})",
+            network_definition.logical_name, header.network_id
+        );
+        println!(
+            "Start epoch        : {}",
+            header.start_epoch_inclusive.number()
+        );
+        println!(
+            "End epoch          : {}",
+            header.end_epoch_exclusive.number()
+        );
+        println!("Nonce              : {}", header.nonce);
+        println!("Notary public key  : {:?}", header.notary_public_key);
+        println!("Notary signatory   : {}", header.notary_is_signatory);
+        println!("Tip percentage     : {}", header.tip_percentage);
+        println!("Blobs              : {}", intent.blobs.blobs.len());
+        println!(
+            "Intent signatures  : {}",
+            transaction.signed_intent.intent_signatures.signatures.len()
+        );
+
+        let validator =
+            NotarizedTransactionValidator::new(ValidationConfig::default(network_definition.id));
+        match validator.validate_from_payload_bytes(&payload) {
+            Ok(_) => println!("Validation         : OK"),
+            Err(err) => println!("Validation         : {:?}", err),
+        }
+
+        println!("Manifest:");
+        println!(
+            "{}",
+            decompile_manifest(&network_definition, &intent.instructions.0)
+        );
+    }
+
+    fn cmd_bls_derive(&self, cmd: &BlsDerive) {
+        let secret_key =
+            BlsSecretKey::from_mnemonic(&cmd.mnemonic, &cmd.passphrase, &cmd.path).unwrap();
+
+        println!("Path            : {}", cmd.path);
+        match &cmd.keystore_out {
+            Some(file) => {
+                let password = new_keystore_password(&cmd.new_password);
+                Keystore::encrypt(&secret_key, &password, &cmd.path)
+                    .unwrap()
+                    .save(file)
+                    .unwrap();
+                println!("Keystore        : {}", file);
+            }
+            None => println!("Secret key      : {}", hex::encode(secret_key.to_bytes())),
+        }
+        println!("Public key      : {}", secret_key.public_key());
+    }
+
+    fn cmd_bls_sign(&self, cmd: &BlsSign) {
+        let secret_key = cmd.key.secret_key();
+        let msg = cmd.msg.bytes();
+        let digest = cmd.prehash.digest(&msg);
+
+        println!("Message         : {}", format_bytes(&msg));
+        print_digest(cmd.prehash, &digest);
+        println!("Public key      : {}", secret_key.public_key());
+        println!("Signature       : {}", secret_key.sign(&digest));
+    }
+
+    fn cmd_bls_keystore_export(&self, cmd: &BlsKeystoreExport) {
+        let secret_key = cmd.key.secret_key();
+        let password = new_keystore_password(&cmd.new_password);
+        let keystore = Keystore::encrypt(&secret_key, &password, &cmd.path).unwrap();
+        keystore.save(&cmd.output).unwrap();
+
+        println!("Keystore        : {}", cmd.output);
+        println!("UUID            : {}", keystore.uuid);
+        println!("Public key      : {}", secret_key.public_key());
+    }
+
+    fn cmd_bls_keystore_import(&self, cmd: &BlsKeystoreImport) {
+        let keystore = Keystore::load(&cmd.keystore).unwrap();
+        let password = cmd
+            .password
+            .clone()
+            .unwrap_or_else(|| read_password(&format!("Password for {}: ", cmd.keystore)));
+        let secret_key = keystore.decrypt(&password).unwrap();
+
+        println!("Keystore        : {}", cmd.keystore);
+        println!("UUID            : {}", keystore.uuid);
+        println!("Path            : {}", keystore.path);
+        println!("Public key      : {}", secret_key.public_key());
+        if cmd.show_secret {
+            println!("Secret key      : {}", hex::encode(secret_key.to_bytes()));
+        }
+    }
+
+    fn cmd_bls_pop_prove(&self, cmd: &BlsPopProve) {
+        let secret_key = cmd.key.secret_key();
+
+        println!("Public key      : {}", secret_key.public_key());
+        println!("PoP             : {}", secret_key.pop_prove());
+    }
+
+    fn cmd_bls_pop_verify(&self, cmd: &BlsPopVerify) {
+        println!("Public key      : {}", cmd.public_key);
+        println!("PoP             : {}", cmd.pop);
+
+        let pub_key = bls::parse_public_key(&cmd.public_key).unwrap();
+        let pop = bls::parse_signature(&cmd.pop).unwrap();
+
+        println!("PoP verify      : {:?}", bls::pop_verify(&pub_key, &pop));
+    }
+
+    fn cmd_bls_pubkey_aggregate(&self, cmd: &BlsPubkeyAggregate) {
+        println!("Public keys     : {:?}", cmd.public_keys);
+
+        let pub_keys: Vec<Bls12381G1PublicKey> = cmd
+            .public_keys
+            .iter()
+            .map(|pk| bls::parse_public_key(pk).unwrap())
+            .collect();
+        let aggregate = bls::aggregate_public_keys(&pub_keys).unwrap();
+        println!("Aggregate key   : {}", aggregate);
+
+        // Fast aggregate verification is the plain verification with the aggregate key
+        if let Some(signature) = &cmd.signature {
+            let signature = bls::parse_signature(signature).unwrap();
+            let digest = cmd.prehash.digest(&cmd.msg.bytes());
+            print_digest(cmd.prehash, &digest);
+            let verified = bls::verify(&digest, &aggregate, &signature);
+            let fast_verified = bls::fast_aggregate_verify(&digest, &pub_keys, &signature);
+            println!("BLS verify with aggregate key : {:?}", verified);
+            println!("BLS fast aggregate verify     : {:?}", fast_verified);
+            assert_eq!(
+                verified, fast_verified,
+                "Aggregate key verification differs from fast aggregate verification"
+            );
+        }
+    }
+
+    fn cmd_threshold_keygen(&self, cmd: &ThresholdKeygen) {
+        let secret_key = cmd.key.secret_key();
+        let shares = split_secret_key(&secret_key, cmd.threshold, cmd.shares).unwrap();
+
+        println!("Threshold       : {} of {}", cmd.threshold, cmd.shares);
+        println!("Group public key: {}", secret_key.public_key());
+        let password = cmd
+            .keystore_dir
+            .as_ref()
+            .map(|_| new_keystore_password(&cmd.new_password));
+        for share in shares {
+            match (&cmd.keystore_dir, &password) {
+                (Some(dir), Some(password)) => {
+                    let file =
+                        std::path::Path::new(dir).join(format!("share-{}.json", share.index));
+                    let file = file.to_str().unwrap();
+                    share.to_keystore(password).unwrap().save(file).unwrap();
+                    println!("Key share       : #{} in {}", share.index, file);
+                }
+                _ => println!("Key share       : {}", share),
+            }
+            println!("  public key    : {}", share.secret_key.public_key());
+        }
+    }
+
+    fn cmd_threshold_sign(&self, cmd: &ThresholdSign) {
+        let share = match (&cmd.share, &cmd.share_keystore) {
+            (Some(share), _) => KeyShare::from_str(share).unwrap(),
+            (None, Some(file)) => {
+                let keystore = Keystore::load(file).unwrap();
+                let password = cmd
+                    .password
+                    .clone()
+                    .unwrap_or_else(|| read_password(&format!("Password for {}: ", file)));
+                KeyShare::from_keystore(&keystore, &password).unwrap()
+            }
+            (None, None) => panic!("Key share or its keystore required"),
+        };
+        let msg = cmd.msg.bytes();
+        let digest = cmd.prehash.digest(&msg);
+        let partial = PartialSignature {
+            index: share.index,
+            signature: share.secret_key.sign(&digest),
+        };
+
+        println!("Message         : {}", format_bytes(&msg));
+        print_digest(cmd.prehash, &digest);
+        println!("Partial sig     : {}", partial);
+    }
+
+    fn cmd_threshold_combine(&self, cmd: &ThresholdCombine) {
+        let partials: Vec<PartialSignature> = cmd
+            .partials
+            .iter()
+            .map(|partial| PartialSignature::from_str(partial).unwrap())
+            .collect();
+        let signature = combine_partial_signatures(&partials).unwrap();
+        println!("Group signature : {}", signature);
+
+        if let Some(public_key) = &cmd.public_key {
+            self.cmd_bls_verify(&BlsVerify {
+                package_address: cmd.package_address.clone(),
+                msg: cmd.msg.clone(),
+                public_key: public_key.clone(),
+                signature: signature.to_string(),
+                prehash: cmd.prehash,
+            });
+        }
+    }
+
+    // Submit each edge case in its own transaction, so that a failure of one case
+    // does not affect the others
+    fn cmd_bls_edge_cases(&self, cmd: &BlsEdgeCases) {
+        if self.dry_run && cmd.output.is_some() {
+            panic!("Report cannot be written in dry-run mode, no transactions are submitted");
+        }
+        let package_address =
+            PackageAddress::try_from_bech32(&self.address_decoder, &cmd.package_address)
+                .expect("Invalid package address");
+
+        let cases: Vec<EdgeCase> = generate_edge_cases()
+            .into_iter()
+            .filter(|case| match &cmd.filter {
+                Some(filter) => case.name.contains(filter.as_str()),
+                None => true,
+            })
+            .collect();
+        if cases.is_empty() {
+            panic!("No edge cases match the filter");
+        }
+
+        println!("Package address : {}", cmd.package_address);
+        println!("Cases           : {}", cases.len());
+
+        let mut outcomes = vec![];
+        for case in &cases {
+            println!();
+            println!("Case            : {}", case.name);
+            println!("Description     : {}", case.description);
+            println!("Function        : {}", case.function);
+
+            let manifest = case
+                .add_to_manifest(
+                    ManifestBuilder::new().lock_fee_from_faucet(),
+                    package_address,
+                )
+                .build();
+
+            if self.dry_run {
+                // Each case is written to its own file, so that they do not overwrite each other
+                let emit_manifest = self
+                    .emit_manifest
+                    .as_deref()
+                    .map(|path| case_manifest_path(path, case.name));
+                self.emit_dry_run_manifest_to(
+                    &manifest,
+                    self.gateway.current_epoch(),
+                    emit_manifest.as_deref(),
+                );
+                continue;
+            }
+
+            let outcome = self.record_edge_case(case, manifest);
+            println!("Outcome         : {}", outcome);
+            outcomes.push(outcome);
+        }
+
+        if let Some(output) = &cmd.output {
+            fs::write(output, serde_json::to_string_pretty(&outcomes).unwrap()).unwrap();
+            println!();
+            println!("Report file     : {}", output);
+        }
+    }
+
+    // Like execute_transaction, but submit and commit failures are recorded, not panicked on
+    fn record_edge_case(
+        &self,
+        case: &EdgeCase,
+        manifest: TransactionManifestV1,
+    ) -> EdgeCaseOutcome {
+        let (intent_hash, result) = self.submit_transaction(manifest, self.gateway.current_epoch());
+        let mut outcome = EdgeCaseOutcome {
+            name: case.name.to_string(),
+            function: case.function.to_string(),
+            intent_hash,
+            status: String::new(),
+            output: None,
+            error: None,
+        };
+
+        match result {
+            Ok(details) => {
+                outcome.status = details.transaction.transaction_status.clone();
+                // Manifest output of the call, which follows the lock_fee instruction
+                outcome.output = details.get_output(1).map(|output| {
+                    hex::decode(output)
+                        .ok()
+                        .and_then(|bytes| {
+                            case.function
+                                .output_type()
+                                .describe(&bytes, &self.address_encoder)
+                        })
+                        .unwrap_or_else(|| format!("0x{}", output))
+                });
+                outcome.error = details.get_error();
+            }
+            Err(SubmitError::Submit(err)) => {
+                outcome.status = "SubmitError".to_string();
+                outcome.error = Some(format!("{:?}", err));
+            }
+            Err(SubmitError::Status(err)) => {
+                outcome.status = "Rejected".to_string();
+                outcome.error = Some(format!("{:?}", err));
+            }
+        }
+        outcome
+    }
+
+    fn cmd_bls_convert(&self, cmd: &BlsConvert) {
+        let info = bls::convert_point(&cmd.point).unwrap_or_else(|err| panic!("{}", err));
+        let engine = base64::engine::general_purpose::STANDARD;
+
+        println!("Group           : {:?}", info.group);
+        println!(
+            "Input           : {}",
+            if info.was_uncompressed {
+                "uncompressed"
+            } else {
+                "compressed"
+            }
+        );
+        println!("On curve        : true");
+        println!("In subgroup     : true");
+        println!("Infinity        : {:?}", info.is_infinity);
+        println!("Compressed      : {}", hex::encode(&info.compressed));
+        println!("Uncompressed    : {}", hex::encode(&info.uncompressed));
+        println!("Base64          : {}", engine.encode(&info.compressed));
+    }
+
+    // Fast aggregate verification trusts that every public key belongs to a signer.
+    // Without PoP the attacker can register the rogue key (attacker_pk - victim_pk), which
+    // aggregates to the attacker key, and sign alone on behalf of the victim.
+    fn cmd_bls_rogue_key_demo(&self, cmd: &BlsRogueKeyDemo) {
+        let msg = cmd.msg.bytes();
+        let digest = cmd.prehash.digest(&msg);
+        let victim_pub_key = bls::parse_public_key(&cmd.victim_public_key).unwrap();
+        let attacker = BlsSecretKey::from_str(&cmd.attacker_secret_key).unwrap();
+        let rogue_pub_key = bls::rogue_public_key(&attacker, &victim_pub_key).unwrap();
+        let pub_keys = [victim_pub_key, rogue_pub_key];
+        let signature = attacker.sign(&digest);
+        // Nobody knows the rogue secret key, so the attacker can only present its own PoP
+        let rogue_pop = attacker.pop_prove();
+
+        println!("Message          : {}", format_bytes(&msg));
+        println!(
+            "Digest           : {} ({})",
+            format_bytes(&digest),
+            cmd.prehash
+        );
+        println!("Victim key       : {}", victim_pub_key);
+        println!("Rogue key        : {}", rogue_pub_key);
+        println!("Forged signature : {}", signature);
+
+        let forged = bls::fast_aggregate_verify(&digest, &pub_keys, &signature);
+        println!("Fast aggregate verify without PoP : {:?}", forged);
+        assert!(
+            forged,
+            "Forged signature shall pass fast aggregate verification"
+        );
+
+        let rogue_pop_valid = bls::pop_verify(&rogue_pub_key, &rogue_pop);
+        println!("Rogue key PoP verify              : {:?}", rogue_pop_valid);
+        assert!(!rogue_pop_valid, "Rogue key PoP shall be rejected");
+        println!("Rogue-key forgery caught by the PoP check");
+
+        if !cmd.submit {
+            return;
+        }
+        let client = self.crypto_scrypto_client(&cmd.package_address);
+        let input = Bls12381V1FastAggregateVerifyInput {
+            message: digest,
+            pub_keys: pub_keys.to_vec(),
+            signature,
+        };
+        let Some(value) = self.call_crypto_scrypto(&client, &input) else {
+            return;
+        };
+        println!("On-ledger fast aggregate verify   : {:?}", value);
+    }
+}
+
+// Show the digest which is actually signed or verified
+fn print_digest(prehash: Prehash, digest: &[u8]) {
+    let digest = match prehash {
+        Prehash::None => format_bytes(digest),
+        _ => hex::encode(digest),
+    };
+    println!("Digest          : {} ({})", digest, prehash);
+}
+
+// Panic unless every public key comes with a valid proof of possession,
+// otherwise fast aggregate verification is prone to rogue-key attacks
+fn check_pops(pub_keys: &[Bls12381G1PublicKey], pops: &[String]) {
+    let pops: Vec<Bls12381G2Signature> = pops
+        .iter()
+        .map(|pop| bls::parse_signature(pop).unwrap())
+        .collect();
+    if let Err(err) = bls::check_pops(pub_keys, &pops) {
+        panic!("Refusing to submit: {}", err);
+    }
+    println!("PoP check       : OK");
+}
+
+fn format_substate_id(id: &SubstateId) -> String {
+    format!(
+        "{} {} partition {} key {}",
+        id.entity_address, id.partition_kind, id.partition_number, id.substate_key
+    )
 }
 
 pub fn run() {
     let cli = Cli::parse();
-    let ctx = CliCtx::new(&cli.network);
+    let ctx = CliCtx::new(&cli);
+
+    if !cli.skip_checks && cli.command.uses_gateway() {
+        ctx.check_gateway();
+    }
 
     match &cli.command {
         Commands::GatewayStatus => {
             ctx.cmd_gateway_status();
         }
+        Commands::Doctor => {
+            ctx.cmd_doctor();
+        }
         Commands::KeccakHash(cmd) => {
             ctx.cmd_keccak_hash(cmd);
         }
@@ -510,5 +2502,68 @@ pub fn run() {
         Commands::FreeXrd(cmd) => {
             ctx.cmd_free_xrd(cmd);
         }
+        Commands::Batch(cmd) => {
+            ctx.cmd_batch(cmd);
+        }
+        Commands::RunManifest(cmd) => {
+            ctx.cmd_run_manifest(cmd);
+        }
+        Commands::Load(cmd) => {
+            ctx.cmd_load(cmd);
+        }
+        Commands::Watch(cmd) => {
+            ctx.cmd_watch(cmd);
+        }
+        Commands::Tx(TxCommands::Details(cmd)) => {
+            ctx.cmd_tx_details(cmd);
+        }
+        Commands::Tx(TxCommands::Status(cmd)) => {
+            ctx.cmd_tx_status(cmd);
+        }
+        Commands::Tx(TxCommands::Output(cmd)) => {
+            ctx.cmd_tx_output(cmd);
+        }
+        Commands::Tx(TxCommands::Decode(cmd)) => {
+            ctx.cmd_tx_decode(cmd);
+        }
+        Commands::Bls(BlsCommands::Derive(cmd)) => {
+            ctx.cmd_bls_derive(cmd);
+        }
+        Commands::Bls(BlsCommands::Sign(cmd)) => {
+            ctx.cmd_bls_sign(cmd);
+        }
+        Commands::Bls(BlsCommands::KeystoreExport(cmd)) => {
+            ctx.cmd_bls_keystore_export(cmd);
+        }
+        Commands::Bls(BlsCommands::KeystoreImport(cmd)) => {
+            ctx.cmd_bls_keystore_import(cmd);
+        }
+        Commands::Bls(BlsCommands::PopProve(cmd)) => {
+            ctx.cmd_bls_pop_prove(cmd);
+        }
+        Commands::Bls(BlsCommands::PopVerify(cmd)) => {
+            ctx.cmd_bls_pop_verify(cmd);
+        }
+        Commands::Threshold(ThresholdCommands::Keygen(cmd)) => {
+            ctx.cmd_threshold_keygen(cmd);
+        }
+        Commands::Threshold(ThresholdCommands::Sign(cmd)) => {
+            ctx.cmd_threshold_sign(cmd);
+        }
+        Commands::Threshold(ThresholdCommands::Combine(cmd)) => {
+            ctx.cmd_threshold_combine(cmd);
+        }
+        Commands::Bls(BlsCommands::PubkeyAggregate(cmd)) => {
+            ctx.cmd_bls_pubkey_aggregate(cmd);
+        }
+        Commands::Bls(BlsCommands::Convert(cmd)) => {
+            ctx.cmd_bls_convert(cmd);
+        }
+        Commands::Bls(BlsCommands::RogueKeyDemo(cmd)) => {
+            ctx.cmd_bls_rogue_key_demo(cmd);
+        }
+        Commands::Bls(BlsCommands::EdgeCases(cmd)) => {
+            ctx.cmd_bls_edge_cases(cmd);
+        }
     }
 }
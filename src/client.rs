@@ -0,0 +1,158 @@
+use crate::crypto_scrypto::*;
+use crate::error::Error;
+use crate::gateway::*;
+use crate::utils::*;
+use transaction::prelude::*;
+
+/// Typed client of the CryptoScrypto package deployed on the ledger.
+///
+/// Each call is a separate transaction notarized with the given key.
+/// The fee is locked from the faucet, so it works on test networks only.
+pub struct CryptoScryptoClient {
+    gateway: GatewayApiClient,
+    network_definition: NetworkDefinition,
+    hash_encoder: TransactionHashBech32Encoder,
+    private_key: Secp256k1PrivateKey,
+    package_address: PackageAddress,
+}
+
+impl CryptoScryptoClient {
+    pub fn new(
+        gateway: GatewayApiClient,
+        network_definition: NetworkDefinition,
+        private_key: Secp256k1PrivateKey,
+        package_address: PackageAddress,
+    ) -> Self {
+        let hash_encoder = TransactionHashBech32Encoder::new(&network_definition);
+        Self {
+            gateway,
+            network_definition,
+            hash_encoder,
+            private_key,
+            package_address,
+        }
+    }
+
+    pub fn package_address(&self) -> PackageAddress {
+        self.package_address
+    }
+
+    pub fn keccak256_hash(
+        &self,
+        data: &[u8],
+    ) -> Result<<Keccak256HashInput as CryptoScryptoFunctionInput>::Output, Error> {
+        let manifest = ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .call_keccak256_hash(self.package_address, data)
+            .build();
+        self.call(manifest)
+    }
+
+    pub fn bls12381_v1_verify(
+        &self,
+        msg: &[u8],
+        pub_key: &Bls12381G1PublicKey,
+        signature: &Bls12381G2Signature,
+    ) -> Result<<Bls12381V1VerifyInput as CryptoScryptoFunctionInput>::Output, Error> {
+        let manifest = ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .call_bls12381_v1_verify(self.package_address, msg, pub_key, signature)
+            .build();
+        self.call(manifest)
+    }
+
+    pub fn bls12381_v1_aggregate_verify(
+        &self,
+        pub_keys_msgs: &[(Bls12381G1PublicKey, Vec<u8>)],
+        signature: &Bls12381G2Signature,
+    ) -> Result<<Bls12381V1AggregateVerifyInput as CryptoScryptoFunctionInput>::Output, Error> {
+        let manifest = ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .call_bls12381_v1_aggregate_verify(self.package_address, pub_keys_msgs, signature)
+            .build();
+        self.call(manifest)
+    }
+
+    pub fn bls12381_v1_fast_aggregate_verify(
+        &self,
+        msg: &[u8],
+        pub_keys: &[Bls12381G1PublicKey],
+        signature: &Bls12381G2Signature,
+    ) -> Result<<Bls12381V1FastAggregateVerifyInput as CryptoScryptoFunctionInput>::Output, Error>
+    {
+        let manifest = ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .call_bls12381_v1_fast_aggregate_verify(self.package_address, msg, pub_keys, signature)
+            .build();
+        self.call(manifest)
+    }
+
+    pub fn bls12381_g2_signature_aggregate(
+        &self,
+        signatures: &[Bls12381G2Signature],
+    ) -> Result<<Bls12381G2SignatureAggregateInput as CryptoScryptoFunctionInput>::Output, Error>
+    {
+        let manifest = ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .call_bls12381_g2_signature_aggregate(self.package_address, signatures)
+            .build();
+        self.call(manifest)
+    }
+
+    /// Call any CryptoScrypto function with its typed input.
+    pub fn call_function<I: CryptoScryptoFunctionInput>(
+        &self,
+        input: &I,
+    ) -> Result<I::Output, Error> {
+        self.call(self.manifest(input))
+    }
+
+    /// Manifest calling the function with its typed input, the fee is locked from the faucet.
+    /// The output of the call is at index 1.
+    pub fn manifest<I: CryptoScryptoFunctionInput>(&self, input: &I) -> TransactionManifestV1 {
+        ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .call_crypto_scrypto(self.package_address, input)
+            .build()
+    }
+
+    /// Execute the manifest and decode the output of its second instruction
+    /// (the first one locks the fee).
+    pub fn call<T: ScryptoDecode>(&self, manifest: TransactionManifestV1) -> Result<T, Error> {
+        let details = self.execute(manifest)?;
+        decode_output(&details, 1)
+    }
+
+    /// Notarize and submit the manifest, then wait until the transaction is committed.
+    /// Random nonce is used, so that the same manifest can be executed again in the same epoch.
+    pub fn execute(&self, manifest: TransactionManifestV1) -> Result<TransactionDetails, Error> {
+        let (notarized_transaction, intent_hash) = create_notarized_transaction_with_nonce(
+            &self.network_definition,
+            self.gateway.current_epoch(),
+            &self.private_key,
+            manifest,
+            rand::random::<u32>(),
+        );
+        let intent_hash = self.hash_encoder.encode(&intent_hash).unwrap();
+
+        self.gateway.transaction_submit(notarized_transaction)?;
+        Ok(self.gateway.wait_for_transaction(&intent_hash)?)
+    }
+}
+
+/// Decode the SBOR-encoded output of the instruction at the given index.
+pub fn decode_output<T: ScryptoDecode>(
+    details: &TransactionDetails,
+    idx: usize,
+) -> Result<T, Error> {
+    match details.get_output(idx) {
+        Some(output) => {
+            let output = hex::decode(output).map_err(|err| Error::InvalidOutput(idx, err))?;
+            Ok(scrypto_decode(&output)?)
+        }
+        None => match details.get_error() {
+            Some(error) => Err(Error::TransactionFailed(error)),
+            None => Err(Error::MissingOutput(idx)),
+        },
+    }
+}
@@ -0,0 +1,240 @@
+use crate::gateway::*;
+use std::fmt;
+use transaction::prelude::*;
+
+/// Gateway API schema versions supported by the client: [min, max)
+pub const MIN_SCHEMA_VERSION: (u32, u32, u32) = (1, 0, 0);
+pub const MAX_SCHEMA_VERSION: (u32, u32, u32) = (2, 0, 0);
+
+/// Result of a single compatibility check.
+#[derive(Clone, Debug)]
+pub struct CheckResult {
+    pub name: &'static str,
+    /// Description of the checked value, either matching or not
+    pub outcome: Result<String, String>,
+}
+
+impl CheckResult {
+    pub fn passed(&self) -> bool {
+        self.outcome.is_ok()
+    }
+}
+
+impl fmt::Display for CheckResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.outcome {
+            Ok(desc) => write!(f, "[ OK ] {:<16}: {}", self.name, desc),
+            Err(desc) => write!(f, "[FAIL] {:<16}: {}", self.name, desc),
+        }
+    }
+}
+
+/// Build the definition of the network served by the gateway.
+/// Network id comes from the network configuration, while the HRP suffix
+/// is taken from the well-known XRD address, eg. "resource_tdx_21_1..." -> "tdx_21_".
+pub fn detect_network_definition(gateway: &GatewayApiClient) -> Result<NetworkDefinition, String> {
+    let status = gateway
+        .gateway_status()
+        .map_err(|err| format!("gateway status error: {}", err.message))?;
+    let configuration = gateway
+        .network_configuration()
+        .map_err(|err| format!("network configuration error: {}", err.message))?;
+
+    if status.ledger_state.network != configuration.network_name {
+        return Err(format!(
+            "ledger network '{}' differs from configured network '{}'",
+            status.ledger_state.network, configuration.network_name
+        ));
+    }
+
+    let xrd = &configuration.well_known_addresses.xrd;
+    // Bech32 separator is the last '1' in the address
+    let hrp_suffix = xrd
+        .rfind('1')
+        .and_then(|separator| xrd[..separator].strip_prefix("resource_"))
+        .ok_or_else(|| format!("cannot get HRP suffix from XRD address '{}'", xrd))?;
+
+    Ok(NetworkDefinition {
+        id: configuration.network_id,
+        logical_name: configuration.network_name,
+        hrp_suffix: hrp_suffix.to_string(),
+    })
+}
+
+/// Check whether the gateway serves the expected network with the supported API
+/// and whether its ledger is up to date.
+/// If the gateway cannot be queried, then the single failed "Gateway" check is returned.
+pub fn check_compatibility(
+    gateway: &GatewayApiClient,
+    network_definition: &NetworkDefinition,
+) -> Vec<CheckResult> {
+    let unreachable = |err: TransactionError| {
+        vec![CheckResult {
+            name: "Gateway",
+            outcome: Err(format!("request failed: {}", err.message)),
+        }]
+    };
+    let status = match gateway.gateway_status() {
+        Ok(status) => status,
+        Err(err) => return unreachable(err),
+    };
+    let configuration = match gateway.network_configuration() {
+        Ok(configuration) => configuration,
+        Err(err) => return unreachable(err),
+    };
+
+    vec![
+        check_network_name(&status, network_definition),
+        check_network_id(&configuration, network_definition),
+        check_schema_version(&status.release_info.open_api_schema_version),
+        check_ledger_age(&status, gateway.max_ledger_age()),
+    ]
+}
+
+fn check_network_name(
+    status: &GatewayStatus,
+    network_definition: &NetworkDefinition,
+) -> CheckResult {
+    let network = &status.ledger_state.network;
+    CheckResult {
+        name: "Network name",
+        outcome: if *network == network_definition.logical_name {
+            Ok(network.clone())
+        } else {
+            Err(format!(
+                "gateway serves '{}', expected '{}'",
+                network, network_definition.logical_name
+            ))
+        },
+    }
+}
+
+fn check_network_id(
+    configuration: &NetworkConfiguration,
+    network_definition: &NetworkDefinition,
+) -> CheckResult {
+    CheckResult {
+        name: "Network id",
+        outcome: if configuration.network_id == network_definition.id {
+            Ok(format!("{:#04x}", configuration.network_id))
+        } else {
+            Err(format!(
+                "gateway serves {:#04x}, expected {:#04x}",
+                configuration.network_id, network_definition.id
+            ))
+        },
+    }
+}
+
+// Whether the version is within [MIN_SCHEMA_VERSION, MAX_SCHEMA_VERSION)
+fn is_supported_version(version: (u32, u32, u32)) -> bool {
+    version >= MIN_SCHEMA_VERSION && version < MAX_SCHEMA_VERSION
+}
+
+fn check_schema_version(version: &str) -> CheckResult {
+    let outcome = match parse_version(version) {
+        Some(parsed) if is_supported_version(parsed) => Ok(version.to_string()),
+        Some(_) => Err(format!(
+            "{} not supported, expected >= {}.{}.{} and < {}.{}.{}",
+            version,
+            MIN_SCHEMA_VERSION.0,
+            MIN_SCHEMA_VERSION.1,
+            MIN_SCHEMA_VERSION.2,
+            MAX_SCHEMA_VERSION.0,
+            MAX_SCHEMA_VERSION.1,
+            MAX_SCHEMA_VERSION.2
+        )),
+        None => Err(format!("cannot parse version '{}'", version)),
+    };
+    CheckResult {
+        name: "Schema version",
+        outcome,
+    }
+}
+
+fn check_ledger_age(status: &GatewayStatus, max_ledger_age: std::time::Duration) -> CheckResult {
+    let outcome = match ledger_age(&status.ledger_state) {
+        Ok(age) if age <= max_ledger_age => Ok(format!(
+            "{} ({} s old)",
+            status.ledger_state.proposer_round_timestamp,
+            age.as_secs()
+        )),
+        Ok(age) => Err(format!(
+            "{} is {} s old, allowed {} s",
+            status.ledger_state.proposer_round_timestamp,
+            age.as_secs(),
+            max_ledger_age.as_secs()
+        )),
+        Err(err) => Err(err),
+    };
+    CheckResult {
+        name: "Ledger state",
+        outcome,
+    }
+}
+
+// Eg. "v1.2.3" or "1.2.3-rc1" -> (1, 2, 3)
+fn parse_version(version: &str) -> Option<(u32, u32, u32)> {
+    let version = version.trim().trim_start_matches('v');
+    let version = version.split(['-', '+']).next()?;
+    let mut parts = version.split('.').map(|part| part.parse::<u32>());
+    let major = parts.next()?.ok()?;
+    let minor = parts.next().unwrap_or(Ok(0)).ok()?;
+    let patch = parts.next().unwrap_or(Ok(0)).ok()?;
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((major, minor, patch))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_version_with_suffixes() {
+        assert_eq!(parse_version("1.2.3"), Some((1, 2, 3)));
+        assert_eq!(parse_version("v1.2.3"), Some((1, 2, 3)));
+        assert_eq!(parse_version(" 1.2.3 "), Some((1, 2, 3)));
+        assert_eq!(parse_version("1.2.3-rc1"), Some((1, 2, 3)));
+        assert_eq!(parse_version("1.2.3-rc.1+build.5"), Some((1, 2, 3)));
+        assert_eq!(parse_version("1.2.3+20231201"), Some((1, 2, 3)));
+        assert_eq!(parse_version("1.2"), Some((1, 2, 0)));
+        assert_eq!(parse_version("1"), Some((1, 0, 0)));
+    }
+
+    #[test]
+    fn parse_version_unparsable() {
+        for version in [
+            "",
+            "v",
+            "abc",
+            "1.x.3",
+            "1..3",
+            "1.2.3.4",
+            "-1.0.0",
+            "1.2.3 beta",
+        ] {
+            assert_eq!(parse_version(version), None, "{}", version);
+        }
+    }
+
+    #[test]
+    fn supported_version_boundaries() {
+        assert!(is_supported_version((1, 0, 0)));
+        assert!(is_supported_version((1, 99, 99)));
+        assert!(!is_supported_version((0, 99, 99)));
+        assert!(!is_supported_version((2, 0, 0)));
+        assert!(!is_supported_version((2, 0, 1)));
+    }
+
+    #[test]
+    fn check_schema_version_outcome() {
+        assert!(check_schema_version("1.0.0").passed());
+        assert!(check_schema_version("v1.5.2-rc1").passed());
+        assert!(!check_schema_version("0.9.9").passed());
+        assert!(!check_schema_version("2.0.0").passed());
+        assert!(!check_schema_version("2.0.0-rc1").passed());
+        assert!(!check_schema_version("unknown").passed());
+    }
+}
@@ -0,0 +1,350 @@
+use scrypto::blueprints::package::{
+    PACKAGE_PUBLISH_WASM_ADVANCED_IDENT, PACKAGE_PUBLISH_WASM_IDENT,
+};
+use std::fmt;
+use transaction::prelude::*;
+
+pub use generated::*;
+
+mod generated {
+    use super::{format_bytes, CryptoScryptoFunctionInput};
+    use transaction::prelude::*;
+
+    include!(concat!(env!("OUT_DIR"), "/crypto_scrypto.rs"));
+}
+
+pub const CRYPTO_SCRYPTO_BLUEPRINT_NAME: &str = "CryptoScrypto";
+
+/// Functions exposed by the CryptoScrypto blueprint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CryptoScryptoFunction {
+    Keccak256Hash,
+    Bls12381V1Verify,
+    Bls12381V1AggregateVerify,
+    Bls12381V1FastAggregateVerify,
+    Bls12381G2SignatureAggregate,
+}
+
+impl CryptoScryptoFunction {
+    pub const ALL: [CryptoScryptoFunction; 5] = [
+        Self::Keccak256Hash,
+        Self::Bls12381V1Verify,
+        Self::Bls12381V1AggregateVerify,
+        Self::Bls12381V1FastAggregateVerify,
+        Self::Bls12381G2SignatureAggregate,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Keccak256Hash => Keccak256HashInput::FUNCTION_NAME,
+            Self::Bls12381V1Verify => Bls12381V1VerifyInput::FUNCTION_NAME,
+            Self::Bls12381V1AggregateVerify => Bls12381V1AggregateVerifyInput::FUNCTION_NAME,
+            Self::Bls12381V1FastAggregateVerify => {
+                Bls12381V1FastAggregateVerifyInput::FUNCTION_NAME
+            }
+            Self::Bls12381G2SignatureAggregate => Bls12381G2SignatureAggregateInput::FUNCTION_NAME,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|f| f.name() == name)
+    }
+
+    /// Human-readable form of the call arguments, decoded into the generated input type.
+    /// Returns None if arguments do not match the function signature.
+    pub fn describe_args(&self, args: &ManifestValue) -> Option<String> {
+        match self {
+            Self::Keccak256Hash => describe_input::<Keccak256HashInput>(args),
+            Self::Bls12381V1Verify => describe_input::<Bls12381V1VerifyInput>(args),
+            Self::Bls12381V1AggregateVerify => {
+                describe_input::<Bls12381V1AggregateVerifyInput>(args)
+            }
+            Self::Bls12381V1FastAggregateVerify => {
+                describe_input::<Bls12381V1FastAggregateVerifyInput>(args)
+            }
+            Self::Bls12381G2SignatureAggregate => {
+                describe_input::<Bls12381G2SignatureAggregateInput>(args)
+            }
+        }
+    }
+
+    /// Output type of the function, as given by the generated input type.
+    pub fn output_type(&self) -> OutputType {
+        match self {
+            Self::Keccak256Hash => output_type_of::<Keccak256HashInput>(),
+            Self::Bls12381V1Verify => output_type_of::<Bls12381V1VerifyInput>(),
+            Self::Bls12381V1AggregateVerify => output_type_of::<Bls12381V1AggregateVerifyInput>(),
+            Self::Bls12381V1FastAggregateVerify => {
+                output_type_of::<Bls12381V1FastAggregateVerifyInput>()
+            }
+            Self::Bls12381G2SignatureAggregate => {
+                output_type_of::<Bls12381G2SignatureAggregateInput>()
+            }
+        }
+    }
+}
+
+fn describe_input<I: CryptoScryptoFunctionInput>(args: &ManifestValue) -> Option<String> {
+    I::from_args(args).map(|input| input.describe())
+}
+
+fn output_type_of<I: CryptoScryptoFunctionInput>() -> OutputType {
+    <I::Output as CryptoScryptoOutput>::OUTPUT_TYPE
+}
+
+impl fmt::Display for CryptoScryptoFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Types returned by the instructions of the manifests built by this tool.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputType {
+    Bool,
+    Hash,
+    Bls12381G2Signature,
+    PackageAddress,
+}
+
+impl OutputType {
+    /// Infer the expected output type from the first known instruction in the manifest.
+    /// Returns the instruction index (equal to the output index) along with the type.
+    pub fn infer(instructions: &[InstructionV1]) -> Option<(usize, OutputType)> {
+        instructions
+            .iter()
+            .enumerate()
+            .find_map(|(idx, instruction)| match instruction {
+                InstructionV1::CallFunction {
+                    package_address: DynamicPackageAddress::Static(address),
+                    function_name,
+                    ..
+                } if *address == PACKAGE_PACKAGE
+                    && (function_name == PACKAGE_PUBLISH_WASM_IDENT
+                        || function_name == PACKAGE_PUBLISH_WASM_ADVANCED_IDENT) =>
+                {
+                    Some((idx, OutputType::PackageAddress))
+                }
+                InstructionV1::CallFunction {
+                    blueprint_name,
+                    function_name,
+                    ..
+                } if blueprint_name == CRYPTO_SCRYPTO_BLUEPRINT_NAME => {
+                    CryptoScryptoFunction::from_name(function_name).map(|f| (idx, f.output_type()))
+                }
+                _ => None,
+            })
+    }
+
+    /// Human-readable form of the SBOR-encoded output.
+    /// Returns None if output does not match the type.
+    pub fn describe(
+        &self,
+        output: &[u8],
+        address_encoder: &AddressBech32Encoder,
+    ) -> Option<String> {
+        let desc = match self {
+            Self::Bool => scrypto_decode::<bool>(output).ok()?.to_string(),
+            Self::Hash => scrypto_decode::<Hash>(output).ok()?.to_string(),
+            Self::Bls12381G2Signature => scrypto_decode::<Bls12381G2Signature>(output)
+                .ok()?
+                .to_string(),
+            Self::PackageAddress => {
+                let address = scrypto_decode::<PackageAddress>(output).ok()?;
+                address_encoder.encode(address.as_ref()).ok()?
+            }
+        };
+        Some(desc)
+    }
+}
+
+/// Output of a blueprint function, which can be described with `OutputType`.
+/// Functions returning other types do not compile until their output type is added.
+pub trait CryptoScryptoOutput: ScryptoDecode {
+    const OUTPUT_TYPE: OutputType;
+}
+
+impl CryptoScryptoOutput for bool {
+    const OUTPUT_TYPE: OutputType = OutputType::Bool;
+}
+
+impl CryptoScryptoOutput for Hash {
+    const OUTPUT_TYPE: OutputType = OutputType::Hash;
+}
+
+impl CryptoScryptoOutput for Bls12381G2Signature {
+    const OUTPUT_TYPE: OutputType = OutputType::Bls12381G2Signature;
+}
+
+impl CryptoScryptoOutput for PackageAddress {
+    const OUTPUT_TYPE: OutputType = OutputType::PackageAddress;
+}
+
+impl FromStr for OutputType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bool" => Ok(Self::Bool),
+            "Hash" | "hash" => Ok(Self::Hash),
+            "Bls12381G2Signature" | "signature" => Ok(Self::Bls12381G2Signature),
+            "PackageAddress" | "package-address" => Ok(Self::PackageAddress),
+            _ => Err(format!(
+                "Unknown output type '{}', expected one of: bool, Hash, Bls12381G2Signature, PackageAddress",
+                s
+            )),
+        }
+    }
+}
+
+/// CryptoScrypto function call found in a transaction manifest.
+#[derive(Clone, Debug)]
+pub struct CryptoScryptoCall {
+    /// Index of the instruction, which is also the index of its output in the receipt
+    pub instruction_index: usize,
+    pub function: CryptoScryptoFunction,
+    pub args: ManifestValue,
+}
+
+impl CryptoScryptoCall {
+    /// Find all calls to the known CryptoScrypto functions of the given package.
+    pub fn find_all(
+        package_address: &PackageAddress,
+        instructions: &[InstructionV1],
+    ) -> Vec<CryptoScryptoCall> {
+        instructions
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, instruction)| match instruction {
+                InstructionV1::CallFunction {
+                    package_address: DynamicPackageAddress::Static(address),
+                    blueprint_name,
+                    function_name,
+                    args,
+                } if address == package_address
+                    && blueprint_name == CRYPTO_SCRYPTO_BLUEPRINT_NAME =>
+                {
+                    CryptoScryptoFunction::from_name(function_name).map(|function| {
+                        CryptoScryptoCall {
+                            instruction_index: idx,
+                            function,
+                            args: args.clone(),
+                        }
+                    })
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Typed input of the blueprint function.
+/// Implementations are generated from the package definition by build.rs.
+pub trait CryptoScryptoFunctionInput: Sized {
+    const BLUEPRINT_NAME: &'static str;
+    const FUNCTION_NAME: &'static str;
+    /// Type returned by the function
+    type Output: CryptoScryptoOutput;
+
+    /// Append the call_function instruction with this input to the manifest
+    fn add_to_manifest(
+        &self,
+        builder: ManifestBuilder,
+        package_address: PackageAddress,
+    ) -> ManifestBuilder;
+
+    /// Decode the input from the call arguments.
+    /// Returns None if arguments do not match the function signature.
+    fn from_args(args: &ManifestValue) -> Option<Self>;
+
+    /// Human-readable form of the input
+    fn describe(&self) -> String;
+}
+
+/// Manifest builder extension to call the CryptoScrypto functions.
+pub trait CryptoScryptoManifestBuilder: Sized {
+    fn call_crypto_scrypto<I: CryptoScryptoFunctionInput>(
+        self,
+        package_address: PackageAddress,
+        input: &I,
+    ) -> Self;
+
+    fn call_keccak256_hash(self, package_address: PackageAddress, data: &[u8]) -> Self {
+        let input = Keccak256HashInput {
+            data: data.to_vec(),
+        };
+        self.call_crypto_scrypto(package_address, &input)
+    }
+
+    fn call_bls12381_v1_verify(
+        self,
+        package_address: PackageAddress,
+        msg: &[u8],
+        pub_key: &Bls12381G1PublicKey,
+        signature: &Bls12381G2Signature,
+    ) -> Self {
+        let input = Bls12381V1VerifyInput {
+            message: msg.to_vec(),
+            pub_key: *pub_key,
+            signature: *signature,
+        };
+        self.call_crypto_scrypto(package_address, &input)
+    }
+
+    fn call_bls12381_v1_aggregate_verify(
+        self,
+        package_address: PackageAddress,
+        pub_keys_msgs: &[(Bls12381G1PublicKey, Vec<u8>)],
+        signature: &Bls12381G2Signature,
+    ) -> Self {
+        let input = Bls12381V1AggregateVerifyInput {
+            pub_keys_msgs: pub_keys_msgs.to_vec(),
+            signature: *signature,
+        };
+        self.call_crypto_scrypto(package_address, &input)
+    }
+
+    fn call_bls12381_v1_fast_aggregate_verify(
+        self,
+        package_address: PackageAddress,
+        msg: &[u8],
+        pub_keys: &[Bls12381G1PublicKey],
+        signature: &Bls12381G2Signature,
+    ) -> Self {
+        let input = Bls12381V1FastAggregateVerifyInput {
+            message: msg.to_vec(),
+            pub_keys: pub_keys.to_vec(),
+            signature: *signature,
+        };
+        self.call_crypto_scrypto(package_address, &input)
+    }
+
+    fn call_bls12381_g2_signature_aggregate(
+        self,
+        package_address: PackageAddress,
+        signatures: &[Bls12381G2Signature],
+    ) -> Self {
+        let input = Bls12381G2SignatureAggregateInput {
+            signatures: signatures.to_vec(),
+        };
+        self.call_crypto_scrypto(package_address, &input)
+    }
+}
+
+impl CryptoScryptoManifestBuilder for ManifestBuilder {
+    fn call_crypto_scrypto<I: CryptoScryptoFunctionInput>(
+        self,
+        package_address: PackageAddress,
+        input: &I,
+    ) -> Self {
+        input.add_to_manifest(self, package_address)
+    }
+}
+
+/// Display bytes as text if they are valid UTF-8, otherwise as hex-encoded string.
+pub fn format_bytes(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(s) if !s.chars().any(|c| c.is_control()) => format!("{:?}", s),
+        _ => format!("0x{}", hex::encode(bytes)),
+    }
+}
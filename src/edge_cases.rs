@@ -0,0 +1,407 @@
+//! Edge-case and malformed inputs of the CryptoScrypto BLS functions.
+//!
+//! Arguments are encoded raw (keys and signatures as plain byte arrays), bypassing the
+//! client-side validation, so that the engine behavior can be observed for each case.
+
+use crate::bls::{self, BlsSecretKey};
+use crate::crypto_scrypto::*;
+use blst::{
+    blst_fp, blst_fp2, blst_fp2_add, blst_fp2_mul, blst_fp2_sqr, blst_fp2_sqrt, blst_fp_add,
+    blst_fp_from_uint64, blst_fp_mul, blst_fp_sqr, blst_fp_sqrt, blst_p1_affine,
+    blst_p1_affine_compress, blst_p1_affine_in_g1, blst_p2_affine, blst_p2_affine_compress,
+    blst_p2_affine_in_g2,
+};
+use serde::Serialize;
+use std::fmt;
+use transaction::prelude::*;
+
+const G1_LENGTH: usize = Bls12381G1PublicKey::LENGTH;
+const G2_LENGTH: usize = Bls12381G2Signature::LENGTH;
+
+/// Input of the CryptoScrypto function, which is malformed or otherwise unusual.
+#[derive(Clone, Debug)]
+pub struct EdgeCase {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub function: CryptoScryptoFunction,
+    /// Raw function arguments
+    pub args: Vec<ManifestValue>,
+}
+
+impl EdgeCase {
+    /// Append the call_function instruction with the raw arguments to the manifest
+    pub fn add_to_manifest(
+        &self,
+        builder: ManifestBuilder,
+        package_address: PackageAddress,
+    ) -> ManifestBuilder {
+        let blueprint_name = CRYPTO_SCRYPTO_BLUEPRINT_NAME;
+        let function_name = self.function.name();
+        match self.args.as_slice() {
+            [a] => builder.call_function(
+                package_address,
+                blueprint_name,
+                function_name,
+                manifest_args!(a),
+            ),
+            [a, b] => builder.call_function(
+                package_address,
+                blueprint_name,
+                function_name,
+                manifest_args!(a, b),
+            ),
+            [a, b, c] => builder.call_function(
+                package_address,
+                blueprint_name,
+                function_name,
+                manifest_args!(a, b, c),
+            ),
+            args => panic!("Unexpected number of arguments: {}", args.len()),
+        }
+    }
+}
+
+/// Engine behavior observed for the edge case.
+#[derive(Clone, Debug, Serialize)]
+pub struct EdgeCaseOutcome {
+    pub name: String,
+    pub function: String,
+    pub intent_hash: String,
+    /// Transaction status, eg. CommittedSuccess, CommittedFailure or Rejected
+    pub status: String,
+    /// Decoded function output, if committed successfully
+    pub output: Option<String>,
+    pub error: Option<String>,
+}
+
+impl fmt::Display for EdgeCaseOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.status)?;
+        if let Some(output) = &self.output {
+            write!(f, ", output: {}", output)?;
+        }
+        if let Some(error) = &self.error {
+            write!(f, ", error: {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+fn raw<T: ManifestEncode + ?Sized>(value: &T) -> ManifestValue {
+    manifest_decode(&manifest_encode(value).unwrap()).unwrap()
+}
+
+// Compressed point at infinity: compression and infinity flags set, all other bits zero
+fn infinity(length: usize) -> Vec<u8> {
+    let mut bytes = vec![0u8; length];
+    bytes[0] = 0xc0;
+    bytes
+}
+
+// Compressed point with x coordinate above the field modulus
+fn x_out_of_field(length: usize) -> Vec<u8> {
+    let mut bytes = vec![0xffu8; length];
+    bytes[0] = 0xbf;
+    bytes
+}
+
+fn fp_from_u64(value: u64) -> blst_fp {
+    let mut fp = blst_fp::default();
+    unsafe {
+        blst_fp_from_uint64(&mut fp, [value, 0, 0, 0, 0, 0].as_ptr());
+    }
+    fp
+}
+
+// Point on the curve y^2 = x^3 + 4, which is outside of the G1 subgroup.
+// Almost any point of the curve is, as the cofactor is large.
+pub(crate) fn g1_not_in_subgroup() -> Vec<u8> {
+    let b = fp_from_u64(4);
+    let mut bytes = vec![0u8; G1_LENGTH];
+    for x in 1..1000 {
+        let x = fp_from_u64(x);
+        let mut x2 = blst_fp::default();
+        let mut x3 = blst_fp::default();
+        let mut rhs = blst_fp::default();
+        let mut y = blst_fp::default();
+        unsafe {
+            blst_fp_sqr(&mut x2, &x);
+            blst_fp_mul(&mut x3, &x2, &x);
+            blst_fp_add(&mut rhs, &x3, &b);
+            if blst_fp_sqrt(&mut y, &rhs) {
+                let point = blst_p1_affine { x, y };
+                if !blst_p1_affine_in_g1(&point) {
+                    blst_p1_affine_compress(bytes.as_mut_ptr(), &point);
+                    return bytes;
+                }
+            }
+        }
+    }
+    panic!("No G1 point outside of the subgroup found");
+}
+
+// Point on the curve y^2 = x^3 + 4(1 + i), which is outside of the G2 subgroup
+pub(crate) fn g2_not_in_subgroup() -> Vec<u8> {
+    let b = blst_fp2 {
+        fp: [fp_from_u64(4), fp_from_u64(4)],
+    };
+    let mut bytes = vec![0u8; G2_LENGTH];
+    for x in 1..1000 {
+        let x = blst_fp2 {
+            fp: [fp_from_u64(x), blst_fp::default()],
+        };
+        let mut x2 = blst_fp2::default();
+        let mut x3 = blst_fp2::default();
+        let mut rhs = blst_fp2::default();
+        let mut y = blst_fp2::default();
+        unsafe {
+            blst_fp2_sqr(&mut x2, &x);
+            blst_fp2_mul(&mut x3, &x2, &x);
+            blst_fp2_add(&mut rhs, &x3, &b);
+            if blst_fp2_sqrt(&mut y, &rhs) {
+                let point = blst_p2_affine { x, y };
+                if !blst_p2_affine_in_g2(&point) {
+                    blst_p2_affine_compress(bytes.as_mut_ptr(), &point);
+                    return bytes;
+                }
+            }
+        }
+    }
+    panic!("No G2 point outside of the subgroup found");
+}
+
+/// Generate the edge cases. Valid keys and signatures are made with fixed secret keys,
+/// so the generated cases are the same on every run.
+pub fn generate_edge_cases() -> Vec<EdgeCase> {
+    use CryptoScryptoFunction::*;
+
+    let sk1 = BlsSecretKey::from_u64(1).unwrap();
+    let sk2 = BlsSecretKey::from_u64(2).unwrap();
+    let msg1 = b"Hello World!".to_vec();
+    let msg2 = b"Goodbye World!".to_vec();
+    let pk1 = sk1.public_key().0.to_vec();
+    let pk2 = sk2.public_key().0.to_vec();
+    let sig1 = sk1.sign(&msg1);
+    let sig2 = sk2.sign(&msg2);
+    let aggregate_sig =
+        |sigs: &[Bls12381G2Signature]| bls::aggregate_signatures(sigs).unwrap().0.to_vec();
+    let same_msg_sig = aggregate_sig(&[sig1, sk2.sign(&msg1)]);
+    let two_msgs_sig = aggregate_sig(&[sig1, sig2]);
+    let double_sig1 = aggregate_sig(&[sig1, sig1]);
+    let pk1_uncompressed = bls::inspect_point(&pk1).unwrap().uncompressed;
+    let sig1 = sig1.0.to_vec();
+    let sig2 = sig2.0.to_vec();
+
+    vec![
+        EdgeCase {
+            name: "verify_valid",
+            description: "Valid signature, baseline",
+            function: Bls12381V1Verify,
+            args: vec![raw(&msg1), raw(&pk1), raw(&sig1)],
+        },
+        EdgeCase {
+            name: "verify_empty_message",
+            description: "Signature of the message verified with empty message",
+            function: Bls12381V1Verify,
+            args: vec![raw(&Vec::<u8>::new()), raw(&pk1), raw(&sig1)],
+        },
+        EdgeCase {
+            name: "verify_identity_pub_key_and_signature",
+            description: "Public key and signature are points at infinity",
+            function: Bls12381V1Verify,
+            args: vec![
+                raw(&msg1),
+                raw(&infinity(G1_LENGTH)),
+                raw(&infinity(G2_LENGTH)),
+            ],
+        },
+        EdgeCase {
+            name: "verify_identity_signature",
+            description: "Signature is point at infinity",
+            function: Bls12381V1Verify,
+            args: vec![raw(&msg1), raw(&pk1), raw(&infinity(G2_LENGTH))],
+        },
+        EdgeCase {
+            name: "verify_pub_key_not_in_subgroup",
+            description: "Public key on the curve, but not in G1 subgroup",
+            function: Bls12381V1Verify,
+            args: vec![raw(&msg1), raw(&g1_not_in_subgroup()), raw(&sig1)],
+        },
+        EdgeCase {
+            name: "verify_signature_not_in_subgroup",
+            description: "Signature on the curve, but not in G2 subgroup",
+            function: Bls12381V1Verify,
+            args: vec![raw(&msg1), raw(&pk1), raw(&g2_not_in_subgroup())],
+        },
+        EdgeCase {
+            name: "verify_pub_key_x_out_of_field",
+            description: "Public key x coordinate above the field modulus",
+            function: Bls12381V1Verify,
+            args: vec![raw(&msg1), raw(&x_out_of_field(G1_LENGTH)), raw(&sig1)],
+        },
+        EdgeCase {
+            name: "verify_pub_key_too_short",
+            description: "Public key of 47 bytes",
+            function: Bls12381V1Verify,
+            args: vec![raw(&msg1), raw(&pk1[..G1_LENGTH - 1]), raw(&sig1)],
+        },
+        EdgeCase {
+            name: "verify_pub_key_too_long",
+            description: "Public key of 49 bytes",
+            function: Bls12381V1Verify,
+            args: vec![
+                raw(&msg1),
+                raw(&[pk1.clone(), vec![0]].concat()),
+                raw(&sig1),
+            ],
+        },
+        EdgeCase {
+            name: "verify_signature_too_short",
+            description: "Signature of 95 bytes",
+            function: Bls12381V1Verify,
+            args: vec![raw(&msg1), raw(&pk1), raw(&sig1[..G2_LENGTH - 1])],
+        },
+        EdgeCase {
+            name: "verify_uncompressed_pub_key",
+            description: "Public key given uncompressed (96 bytes)",
+            function: Bls12381V1Verify,
+            args: vec![raw(&msg1), raw(&pk1_uncompressed), raw(&sig1)],
+        },
+        EdgeCase {
+            name: "aggregate_verify_empty",
+            description: "No public keys and messages",
+            function: Bls12381V1AggregateVerify,
+            args: vec![
+                raw(&Vec::<(Vec<u8>, Vec<u8>)>::new()),
+                raw(&infinity(G2_LENGTH)),
+            ],
+        },
+        EdgeCase {
+            name: "aggregate_verify_truncated",
+            description: "Messages and public keys of different lengths, zipped into fewer pairs",
+            function: Bls12381V1AggregateVerify,
+            args: vec![raw(&vec![(pk1.clone(), msg1.clone())]), raw(&two_msgs_sig)],
+        },
+        EdgeCase {
+            name: "aggregate_verify_duplicate_messages",
+            description: "Different public keys signing the same message",
+            function: Bls12381V1AggregateVerify,
+            args: vec![
+                raw(&vec![
+                    (pk1.clone(), msg1.clone()),
+                    (pk2.clone(), msg1.clone()),
+                ]),
+                raw(&same_msg_sig),
+            ],
+        },
+        EdgeCase {
+            name: "aggregate_verify_duplicate_pairs",
+            description: "The same public key and message given twice",
+            function: Bls12381V1AggregateVerify,
+            args: vec![
+                raw(&vec![
+                    (pk1.clone(), msg1.clone()),
+                    (pk1.clone(), msg1.clone()),
+                ]),
+                raw(&double_sig1),
+            ],
+        },
+        EdgeCase {
+            name: "fast_aggregate_verify_empty",
+            description: "No public keys",
+            function: Bls12381V1FastAggregateVerify,
+            args: vec![
+                raw(&msg1),
+                raw(&Vec::<Vec<u8>>::new()),
+                raw(&infinity(G2_LENGTH)),
+            ],
+        },
+        EdgeCase {
+            name: "fast_aggregate_verify_duplicate_pub_keys",
+            description: "The same public key given twice",
+            function: Bls12381V1FastAggregateVerify,
+            args: vec![
+                raw(&msg1),
+                raw(&vec![pk1.clone(), pk1.clone()]),
+                raw(&double_sig1),
+            ],
+        },
+        EdgeCase {
+            name: "fast_aggregate_verify_identity_pub_key",
+            description: "Point at infinity among the public keys",
+            function: Bls12381V1FastAggregateVerify,
+            args: vec![
+                raw(&msg1),
+                raw(&vec![pk1.clone(), infinity(G1_LENGTH)]),
+                raw(&sig1),
+            ],
+        },
+        EdgeCase {
+            name: "fast_aggregate_verify_pub_key_not_in_subgroup",
+            description: "Public key outside of G1 subgroup among the public keys",
+            function: Bls12381V1FastAggregateVerify,
+            args: vec![
+                raw(&msg1),
+                raw(&vec![pk1.clone(), g1_not_in_subgroup()]),
+                raw(&same_msg_sig),
+            ],
+        },
+        EdgeCase {
+            name: "signature_aggregate_empty",
+            description: "No signatures",
+            function: Bls12381G2SignatureAggregate,
+            args: vec![raw(&Vec::<Vec<u8>>::new())],
+        },
+        EdgeCase {
+            name: "signature_aggregate_identity",
+            description: "Point at infinity among the signatures",
+            function: Bls12381G2SignatureAggregate,
+            args: vec![raw(&vec![sig1.clone(), infinity(G2_LENGTH)])],
+        },
+        EdgeCase {
+            name: "signature_aggregate_not_in_subgroup",
+            description: "Signature outside of G2 subgroup among the signatures",
+            function: Bls12381G2SignatureAggregate,
+            args: vec![raw(&vec![sig1.clone(), g2_not_in_subgroup()])],
+        },
+        EdgeCase {
+            name: "signature_aggregate_wrong_length",
+            description: "Signature of 95 bytes among the signatures",
+            function: Bls12381G2SignatureAggregate,
+            args: vec![raw(&vec![sig1.clone(), sig2[..G2_LENGTH - 1].to_vec()])],
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn points_not_in_subgroup_are_on_curve() {
+        for (bytes, group) in [
+            (g1_not_in_subgroup(), bls::PointGroup::G1),
+            (g2_not_in_subgroup(), bls::PointGroup::G2),
+        ] {
+            let info = bls::inspect_point(&bytes).unwrap();
+            assert_eq!(info.group, group);
+            assert!(!info.in_subgroup);
+            assert!(!info.is_infinity);
+        }
+    }
+
+    #[test]
+    fn x_out_of_field_fails_to_decode() {
+        assert!(bls::inspect_point(&x_out_of_field(G1_LENGTH)).is_err());
+        assert!(bls::inspect_point(&x_out_of_field(G2_LENGTH)).is_err());
+    }
+
+    #[test]
+    fn infinity_decodes_as_infinity() {
+        for length in [G1_LENGTH, G2_LENGTH] {
+            let info = bls::inspect_point(&infinity(length)).unwrap();
+            assert!(info.in_subgroup);
+            assert!(info.is_infinity);
+        }
+    }
+}
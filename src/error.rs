@@ -0,0 +1,44 @@
+use crate::gateway::TransactionError;
+use std::fmt;
+use transaction::prelude::*;
+
+/// Errors returned by the `CryptoScryptoClient`.
+#[derive(Debug)]
+pub enum Error {
+    /// Gateway rejected the request
+    Gateway(TransactionError),
+    /// Transaction was committed, but its execution failed
+    TransactionFailed(String),
+    /// Transaction receipt has no output at the given index
+    MissingOutput(usize),
+    /// Output at the given index returned by the gateway is not valid hex
+    InvalidOutput(usize, hex::FromHexError),
+    /// Output does not match the expected type
+    Decode(DecodeError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Gateway(err) => write!(f, "gateway error: {} ({})", err.message, err.code),
+            Self::TransactionFailed(err) => write!(f, "transaction failed: {}", err),
+            Self::MissingOutput(idx) => write!(f, "no output at index {}", idx),
+            Self::InvalidOutput(idx, err) => write!(f, "invalid output at index {}: {}", idx, err),
+            Self::Decode(err) => write!(f, "output decode error: {:?}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<TransactionError> for Error {
+    fn from(err: TransactionError) -> Self {
+        Self::Gateway(err)
+    }
+}
+
+impl From<DecodeError> for Error {
+    fn from(err: DecodeError) -> Self {
+        Self::Decode(err)
+    }
+}
@@ -1,11 +1,266 @@
-use reqwest::{blocking, header::*};
-use serde::{Deserialize, Serialize};
+use rand::Rng;
+use reqwest::{blocking, header::*, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::{fmt, thread, time};
 use transaction::prelude::*;
 
+// Gateways whose ledger is older than that are considered unhealthy
+pub(crate) const DEFAULT_MAX_LEDGER_AGE: time::Duration = time::Duration::from_secs(60);
+
 #[derive(Clone, Debug)]
 pub struct GatewayApiClient {
-    url: String,
+    urls: Vec<String>,
+    // Index of the gateway the requests are routed to, shared between clones
+    active: Arc<AtomicUsize>,
     client: blocking::Client,
+    retry_config: RetryConfig,
+    max_ledger_age: time::Duration,
+    event_hook: Option<EventHook>,
+}
+
+/// Failover event reported to the hook set with `GatewayApiClient::with_event_hook`.
+#[derive(Clone, Debug)]
+pub enum GatewayEvent {
+    /// Request to the gateway failed after all retries
+    RequestFailed { url: String, error: String },
+    /// Gateway failed the health probe
+    Unhealthy { url: String, error: String },
+    /// Requests are routed to the gateway from now on
+    Selected { url: String },
+}
+
+impl fmt::Display for GatewayEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RequestFailed { url, error } => write!(f, "Gateway {} failed: {}", url, error),
+            Self::Unhealthy { url, error } => write!(f, "Gateway {} unhealthy: {}", url, error),
+            Self::Selected { url } => write!(f, "Selected gateway: {}", url),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct EventHook(Arc<dyn Fn(&GatewayEvent) + Send + Sync>);
+
+impl EventHook {
+    pub(crate) fn new<F: Fn(&GatewayEvent) + Send + Sync + 'static>(hook: F) -> Self {
+        Self(Arc::new(hook))
+    }
+
+    pub(crate) fn report(hook: &Option<EventHook>, event: GatewayEvent) {
+        if let Some(EventHook(hook)) = hook {
+            hook(&event);
+        }
+    }
+}
+
+impl fmt::Debug for EventHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EventHook")
+    }
+}
+
+/// HTTP settings of the gateway client.
+/// Fields left as None keep the reqwest defaults.
+#[derive(Clone, Debug, Default)]
+pub struct GatewayClientConfig {
+    pub connect_timeout: Option<time::Duration>,
+    /// Timeout of the whole request, from connecting until the response body is read
+    pub timeout: Option<time::Duration>,
+    /// Proxy for all requests, eg. "http://proxy.example.com:8080"
+    pub proxy: Option<String>,
+    /// PEM file with the additional root certificate to trust
+    pub ca_bundle: Option<String>,
+    pub user_agent: Option<String>,
+    /// Headers sent with every request, eg. authorization or API key
+    pub headers: Vec<(String, String)>,
+}
+
+impl GatewayClientConfig {
+    fn header_map(&self) -> Result<HeaderMap, String> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.headers {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|err| format!("invalid header name '{}': {}", name, err))?;
+            let value = HeaderValue::from_str(value)
+                .map_err(|err| format!("invalid header value for '{}': {}", name, err))?;
+            headers.insert(name, value);
+        }
+        Ok(headers)
+    }
+
+    fn root_certificate(&self) -> Result<Option<reqwest::Certificate>, String> {
+        match &self.ca_bundle {
+            Some(path) => {
+                let pem = std::fs::read(path)
+                    .map_err(|err| format!("cannot read CA bundle '{}': {}", path, err))?;
+                reqwest::Certificate::from_pem(&pem)
+                    .map(Some)
+                    .map_err(|err| format!("invalid CA bundle '{}': {}", path, err))
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub fn build_blocking_client(&self) -> Result<blocking::Client, String> {
+        self.configure(blocking::Client::builder())?
+            .build()
+            .map_err(|err| err.to_string())
+    }
+
+    // Apply the settings to the client builder, so that blocking and async clients
+    // are configured the same way
+    pub(crate) fn configure<B: HttpClientBuilder>(&self, builder: B) -> Result<B, String> {
+        let mut builder = builder.default_headers(self.header_map()?);
+        if let Some(connect_timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(proxy) = &self.proxy {
+            let proxy = reqwest::Proxy::all(proxy)
+                .map_err(|err| format!("invalid proxy '{}': {}", proxy, err))?;
+            builder = builder.proxy(proxy);
+        }
+        if let Some(certificate) = self.root_certificate()? {
+            builder = builder.add_root_certificate(certificate);
+        }
+        if let Some(user_agent) = &self.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+        Ok(builder)
+    }
+}
+
+// Settings common to the blocking and async reqwest client builders
+pub(crate) trait HttpClientBuilder: Sized {
+    fn default_headers(self, headers: HeaderMap) -> Self;
+    fn connect_timeout(self, timeout: time::Duration) -> Self;
+    fn timeout(self, timeout: time::Duration) -> Self;
+    fn proxy(self, proxy: reqwest::Proxy) -> Self;
+    fn add_root_certificate(self, certificate: reqwest::Certificate) -> Self;
+    fn user_agent(self, user_agent: &str) -> Self;
+}
+
+macro_rules! impl_http_client_builder {
+    ($builder:ty) => {
+        impl HttpClientBuilder for $builder {
+            fn default_headers(self, headers: HeaderMap) -> Self {
+                <$builder>::default_headers(self, headers)
+            }
+            fn connect_timeout(self, timeout: time::Duration) -> Self {
+                <$builder>::connect_timeout(self, timeout)
+            }
+            fn timeout(self, timeout: time::Duration) -> Self {
+                <$builder>::timeout(self, timeout)
+            }
+            fn proxy(self, proxy: reqwest::Proxy) -> Self {
+                <$builder>::proxy(self, proxy)
+            }
+            fn add_root_certificate(self, certificate: reqwest::Certificate) -> Self {
+                <$builder>::add_root_certificate(self, certificate)
+            }
+            fn user_agent(self, user_agent: &str) -> Self {
+                <$builder>::user_agent(self, user_agent)
+            }
+        }
+    };
+}
+
+impl_http_client_builder!(blocking::ClientBuilder);
+impl_http_client_builder!(reqwest::ClientBuilder);
+
+/// Retry policy for requests failing with transport errors, 5xx or 429 responses.
+#[derive(Clone, Debug)]
+pub struct RetryConfig {
+    /// Number of retries after the first attempt (0 disables retries)
+    pub max_retries: u32,
+    /// Backoff before the first retry, doubled with every next retry
+    pub initial_backoff: time::Duration,
+    /// Upper limit of the backoff
+    pub max_backoff: time::Duration,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: time::Duration::from_millis(500),
+            max_backoff: time::Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Delay before the retry with given number (starting from 0).
+    /// Delay requested by the gateway with "Retry-After" header takes precedence,
+    /// but it is limited to `max_backoff` as well.
+    pub fn backoff(&self, retry: u32, retry_after: Option<time::Duration>) -> time::Duration {
+        if let Some(retry_after) = retry_after {
+            return retry_after.min(self.max_backoff);
+        }
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_backoff);
+
+        // Jitter spreads retries of concurrent clients: random delay between
+        // a half and the full backoff
+        let jitter_range = backoff.as_millis() as u64 / 2;
+        backoff - time::Duration::from_millis(rand::thread_rng().gen_range(0..=jitter_range))
+    }
+}
+
+// Gateway is considered healthy if its ledger is not older than the given age
+pub(crate) fn check_ledger_age(
+    status: &GatewayStatus,
+    max_ledger_age: time::Duration,
+) -> Result<(), String> {
+    let age = ledger_age(&status.ledger_state)?;
+    if age > max_ledger_age {
+        return Err(format!("ledger is stale, {} s old", age.as_secs()));
+    }
+    Ok(())
+}
+
+// Parse the response body, which is either the expected response or the gateway error.
+// Body matching neither of them is reported as invalid response.
+pub(crate) fn parse_response<R: DeserializeOwned>(resp: &str) -> Result<R, TransactionError> {
+    serde_json::from_str::<R>(resp).map_err(|err| {
+        serde_json::from_str::<TransactionError>(resp).unwrap_or_else(|_| {
+            TransactionError::client_error("InvalidResponse", format!("invalid response: {}", err))
+        })
+    })
+}
+
+// Age of the ledger state, based on the timestamp of the last proposer round
+pub fn ledger_age(ledger_state: &LedgerState) -> Result<time::Duration, String> {
+    let timestamp = chrono::DateTime::parse_from_rfc3339(&ledger_state.proposer_round_timestamp)
+        .map_err(|err| format!("invalid proposer round timestamp: {}", err))?;
+    // Gateway clock may be slightly ahead of ours
+    Ok((chrono::Utc::now() - timestamp.with_timezone(&chrono::Utc))
+        .to_std()
+        .unwrap_or_default())
+}
+
+pub fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+// Only "Retry-After" given in seconds is supported, HTTP-date is ignored
+pub fn parse_retry_after(headers: &HeaderMap) -> Option<time::Duration> {
+    headers
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(time::Duration::from_secs)
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -30,6 +285,19 @@ pub struct GatewayStatus {
     pub release_info: ReleaseInfo,
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct WellKnownAddresses {
+    pub xrd: String,
+    pub faucet: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct NetworkConfiguration {
+    pub network_id: u8,
+    pub network_name: String,
+    pub well_known_addresses: WellKnownAddresses,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ErrorDetails {
     pub r#type: String,
@@ -62,6 +330,28 @@ pub struct TransactionError {
     pub trace_id: String,
 }
 
+impl TransactionError {
+    // Error of the request, which got no valid response from any of the gateways
+    pub(crate) fn request_failed(message: String) -> Self {
+        Self::client_error("RequestFailed", message)
+    }
+
+    // Error detected by the client rather than reported by the gateway
+    fn client_error(r#type: &str, message: String) -> Self {
+        Self {
+            message,
+            code: 0,
+            details: ErrorDetails {
+                r#type: r#type.to_string(),
+                address: None,
+                exception: None,
+                cause: None,
+            },
+            trace_id: String::new(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct TransactionStatus {
     // transaction status 200
@@ -80,11 +370,98 @@ pub struct TransactionOutput {
     pub programmatic_json: Option<String>,
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EntityReference {
+    pub entity_type: String,
+    pub is_global: bool,
+    pub entity_address: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "type")]
+pub enum EventEmitter {
+    Method {
+        entity: EntityReference,
+        object_module_id: String,
+    },
+    Function {
+        package_address: String,
+        blueprint_name: String,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TransactionEvent {
+    pub name: String,
+    pub emitter: EventEmitter,
+    // Programmatic JSON representation of the SBOR-encoded event payload
+    pub data: serde_json::Value,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FeeSummary {
+    pub execution_cost_units_consumed: u64,
+    pub finalization_cost_units_consumed: u64,
+    pub xrd_total_execution_cost: String,
+    pub xrd_total_finalization_cost: String,
+    pub xrd_total_royalty_cost: String,
+    pub xrd_total_storage_cost: String,
+    pub xrd_total_tipping_cost: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SubstateId {
+    pub entity_type: String,
+    pub entity_address: String,
+    pub partition_kind: String,
+    pub partition_number: u8,
+    // Key is one of field/map/sorted key, each with its own JSON shape
+    pub substate_key: serde_json::Value,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SubstateValue {
+    pub substate_hex: String,
+    pub substate_data_hash: String,
+    pub substate_data: Option<serde_json::Value>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CreatedSubstate {
+    pub substate_id: SubstateId,
+    pub value: SubstateValue,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct UpdatedSubstate {
+    pub substate_id: SubstateId,
+    pub new_value: SubstateValue,
+    pub previous_value: Option<SubstateValue>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DeletedSubstate {
+    pub substate_id: SubstateId,
+    pub previous_value: Option<SubstateValue>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct StateUpdates {
+    pub created_substates: Vec<CreatedSubstate>,
+    pub updated_substates: Vec<UpdatedSubstate>,
+    pub deleted_substates: Vec<DeletedSubstate>,
+    pub new_global_entities: Vec<EntityReference>,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct TransactionReceipt {
     pub status: String,
     pub output: Option<Vec<TransactionOutput>>,
     pub error_message: Option<String>,
+    // Below fields are returned only if requested with TransactionDetailsOptIns
+    pub events: Option<Vec<TransactionEvent>>,
+    pub state_updates: Option<StateUpdates>,
+    pub fee_summary: Option<FeeSummary>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -99,6 +476,8 @@ pub struct TransactionDetailsStatus {
     pub fee_paid: String,
     pub confirmed_at: String,
     pub receipt: TransactionReceipt,
+    pub raw_hex: Option<String>,
+    pub manifest_instructions: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -107,6 +486,93 @@ pub struct TransactionDetails {
     pub transaction: TransactionDetailsStatus,
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TransactionDetailsOptIns {
+    pub raw_hex: bool,
+    pub receipt_output: bool,
+    pub receipt_events: bool,
+    pub receipt_state_changes: bool,
+    pub receipt_fee_summary: bool,
+    pub manifest_instructions: bool,
+}
+
+impl Default for TransactionDetailsOptIns {
+    fn default() -> Self {
+        // Gateway returns the receipt output unless explicitly disabled
+        Self {
+            raw_hex: false,
+            receipt_output: true,
+            receipt_events: false,
+            receipt_state_changes: false,
+            receipt_fee_summary: false,
+            manifest_instructions: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TransactionDetailsRequest {
+    pub intent_hash: String,
+    pub opt_ins: TransactionDetailsOptIns,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LedgerStateSelector {
+    pub state_version: u64,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct StreamTransactionsRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_ledger_state: Option<LedgerStateSelector>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit_per_page: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind_filter: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub affected_global_entities_filter: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order: Option<String>,
+    pub opt_ins: TransactionDetailsOptIns,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CommittedTransactionInfo {
+    pub state_version: u64,
+    pub epoch: u64,
+    pub round: u64,
+    pub round_timestamp: String,
+    pub transaction_status: String,
+    pub payload_hash: Option<String>,
+    pub intent_hash: Option<String>,
+    pub fee_paid: Option<String>,
+    pub confirmed_at: Option<String>,
+    pub error_message: Option<String>,
+    pub raw_hex: Option<String>,
+    pub receipt: Option<TransactionReceipt>,
+    pub manifest_instructions: Option<String>,
+}
+
+impl CommittedTransactionInfo {
+    pub fn get_output(&self, idx: usize) -> Option<String> {
+        self.receipt
+            .as_ref()?
+            .output
+            .as_ref()?
+            .get(idx)
+            .map(|t| t.hex.clone())
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct StreamTransactions {
+    pub ledger_state: LedgerState,
+    pub next_cursor: Option<String>,
+    pub items: Vec<CommittedTransactionInfo>,
+}
+
 impl TransactionDetails {
     pub fn get_output(&self, idx: usize) -> Option<String> {
         self.transaction
@@ -124,31 +590,212 @@ impl TransactionDetails {
 
 impl GatewayApiClient {
     pub fn new(url: &str) -> Self {
+        Self::with_urls(&[url])
+    }
+
+    /// Client failing over between given gateways.
+    /// Requests are routed to the first one until it fails.
+    pub fn with_urls<S: AsRef<str>>(urls: &[S]) -> Self {
+        assert!(!urls.is_empty(), "At least one gateway URL required");
         Self {
-            url: url.to_string(),
+            urls: urls.iter().map(|url| url.as_ref().to_string()).collect(),
+            active: Arc::new(AtomicUsize::new(0)),
             client: blocking::Client::new(),
+            retry_config: RetryConfig::default(),
+            max_ledger_age: DEFAULT_MAX_LEDGER_AGE,
+            event_hook: None,
         }
     }
 
-    pub fn gateway_status(&self) -> GatewayStatus {
+    pub fn with_client_config(mut self, config: &GatewayClientConfig) -> Self {
+        self.client = config
+            .build_blocking_client()
+            .unwrap_or_else(|err| panic!("Invalid gateway client configuration: {}", err));
+        self
+    }
+
+    pub fn with_retry_config(mut self, retry_config: RetryConfig) -> Self {
+        self.retry_config = retry_config;
+        self
+    }
+
+    pub fn with_max_ledger_age(mut self, max_ledger_age: time::Duration) -> Self {
+        self.max_ledger_age = max_ledger_age;
+        self
+    }
+
+    /// Call the hook on failover events, eg. to log them
+    pub fn with_event_hook<F: Fn(&GatewayEvent) + Send + Sync + 'static>(
+        mut self,
+        hook: F,
+    ) -> Self {
+        self.event_hook = Some(EventHook::new(hook));
+        self
+    }
+
+    fn report(&self, event: GatewayEvent) {
+        EventHook::report(&self.event_hook, event);
+    }
+
+    pub fn urls(&self) -> &[String] {
+        &self.urls
+    }
+
+    /// URL of the gateway the requests are currently routed to
+    pub fn url(&self) -> &str {
+        &self.urls[self.active.load(Ordering::SeqCst)]
+    }
+
+    /// Probe the gateway with the status request (no retries).
+    /// Gateway is healthy if it responds and its ledger is not stale.
+    pub fn check_health(&self, url: &str) -> Result<GatewayStatus, String> {
         let resp = self
             .client
-            .post(self.url.clone() + "/status/gateway-status")
+            .post(url.to_string() + "/status/gateway-status")
             .header(ACCEPT, "application/json")
             .header(CONTENT_TYPE, "application/json")
             .send()
-            .unwrap()
-            .text()
-            .unwrap();
+            .map_err(|err| format!("request error: {}", err))?;
+        if !resp.status().is_success() {
+            return Err(format!("response status {}", resp.status()));
+        }
+        let status: GatewayStatus = resp
+            .json()
+            .map_err(|err| format!("invalid response: {}", err))?;
+
+        check_ledger_age(&status, self.max_ledger_age)?;
+        Ok(status)
+    }
+
+    /// Route requests to the first healthy gateway, starting from the currently active one.
+    /// Returns false if none of the gateways is healthy.
+    pub fn select_healthy_gateway(&self) -> bool {
+        self.select_healthy_gateway_except(&HashSet::new())
+    }
+
+    // Like select_healthy_gateway, but gateways with the given indices are skipped
+    fn select_healthy_gateway_except(&self, excluded: &HashSet<usize>) -> bool {
+        let current = self.active.load(Ordering::SeqCst);
+        for offset in 0..self.urls.len() {
+            let idx = (current + offset) % self.urls.len();
+            if excluded.contains(&idx) {
+                continue;
+            }
+            match self.check_health(&self.urls[idx]) {
+                Ok(_) => {
+                    self.report(GatewayEvent::Selected {
+                        url: self.urls[idx].clone(),
+                    });
+                    self.active.store(idx, Ordering::SeqCst);
+                    return true;
+                }
+                Err(error) => self.report(GatewayEvent::Unhealthy {
+                    url: self.urls[idx].clone(),
+                    error,
+                }),
+            }
+        }
+        false
+    }
+
+    // Send the request to the active gateway. If it keeps failing after all retries,
+    // then switch to another healthy gateway and try again. Each gateway is tried
+    // at most once per request, the error is returned when all of them failed.
+    fn post<B: Serialize + ?Sized>(
+        &self,
+        path: &str,
+        body: Option<&B>,
+    ) -> Result<(String, u32), TransactionError> {
+        let mut total_retries = 0;
+        let mut tried = HashSet::new();
+        loop {
+            let idx = self.active.load(Ordering::SeqCst);
+            let url = self.urls[idx].clone();
+            match self.post_to(&url, path, body) {
+                Ok((resp, retries)) => return Ok((resp, total_retries + retries)),
+                Err((error, retries)) => {
+                    total_retries += retries + 1;
+                    tried.insert(idx);
+                    self.report(GatewayEvent::RequestFailed {
+                        url,
+                        error: error.clone(),
+                    });
+                    if tried.len() == self.urls.len() || !self.select_healthy_gateway_except(&tried)
+                    {
+                        return Err(TransactionError::request_failed(error));
+                    }
+                }
+            }
+        }
+    }
+
+    // Send the request and retry it on transport errors, 5xx and 429 responses.
+    // Returns the response body along with the number of retries made,
+    // or the last error if the gateway still fails after all retries.
+    fn post_to<B: Serialize + ?Sized>(
+        &self,
+        url: &str,
+        path: &str,
+        body: Option<&B>,
+    ) -> Result<(String, u32), (String, u32)> {
+        let mut retry = 0;
+        loop {
+            let mut request = self
+                .client
+                .post(url.to_string() + path)
+                .header(ACCEPT, "application/json")
+                .header(CONTENT_TYPE, "application/json");
+            if let Some(body) = body {
+                request = request.json(body);
+            }
+
+            let retry_after = match request.send() {
+                Ok(resp) if !is_retryable_status(resp.status()) => {
+                    return resp
+                        .text()
+                        .map(|text| (text, retry))
+                        .map_err(|err| (format!("response body error: {:?}", err), retry))
+                }
+                Ok(resp) if retry >= self.retry_config.max_retries => {
+                    return Err((format!("response status {}", resp.status()), retry))
+                }
+                Ok(resp) => parse_retry_after(resp.headers()),
+                Err(err) if retry >= self.retry_config.max_retries => {
+                    return Err((format!("request error: {:?}", err), retry))
+                }
+                Err(_) => None,
+            };
+
+            thread::sleep(self.retry_config.backoff(retry, retry_after));
+            retry += 1;
+        }
+    }
+
+    pub fn gateway_status(&self) -> Result<GatewayStatus, TransactionError> {
+        let (resp, _) = self.post::<()>("/status/gateway-status", None)?;
+
+        parse_response(&resp)
+    }
+
+    pub fn network_configuration(&self) -> Result<NetworkConfiguration, TransactionError> {
+        let (resp, _) = self.post::<()>("/status/network-configuration", None)?;
 
-        let status: GatewayStatus = serde_json::from_str(&resp).unwrap();
-        status
+        parse_response(&resp)
     }
 
+    pub fn max_ledger_age(&self) -> time::Duration {
+        self.max_ledger_age
+    }
+
+    // Panics if the gateway status cannot be got
     pub fn current_epoch(&self) -> u64 {
-        self.gateway_status().ledger_state.epoch
+        self.gateway_status()
+            .unwrap_or_else(|err| panic!("gateway request error: {}", err.message))
+            .ledger_state
+            .epoch
     }
 
+    // Submission is retried as well, since the same payload always has the same intent hash.
     pub fn transaction_submit(
         &self,
         transaction: NotarizedTransactionV1,
@@ -159,19 +806,13 @@ impl GatewayApiClient {
         let mut map = HashMap::new();
         map.insert("notarized_transaction_hex", notarized_transaction_hex);
 
-        let resp = self
-            .client
-            .post(self.url.clone() + "/transaction/submit")
-            .header(ACCEPT, "application/json")
-            .header(CONTENT_TYPE, "application/json")
-            .json(&map)
-            .send()
-            .unwrap()
-            .text()
-            .unwrap();
+        let (resp, retries) = self.post("/transaction/submit", Some(&map))?;
 
-        serde_json::from_str::<TransactionSubmit>(&resp)
-            .map_err(|_| serde_json::from_str::<TransactionError>(&resp).unwrap())
+        parse_response::<TransactionSubmit>(&resp).map(|submit| TransactionSubmit {
+            // If the payload is reported as duplicate after a retry, then it was
+            // submitted by one of the previous attempts, whose response was lost.
+            duplicate: submit.duplicate && retries == 0,
+        })
     }
 
     pub fn transaction_status(
@@ -181,40 +822,160 @@ impl GatewayApiClient {
         let mut map = HashMap::new();
         map.insert("intent_hash", intent_hash);
 
-        let resp = self
-            .client
-            .post(self.url.clone() + "/transaction/status")
-            .header(ACCEPT, "application/json")
-            .header(CONTENT_TYPE, "application/json")
-            .json(&map)
-            .send()
-            .unwrap()
-            .text()
-            .unwrap();
+        let (resp, _) = self.post("/transaction/status", Some(&map))?;
 
-        serde_json::from_str::<TransactionStatus>(&resp)
-            .map_err(|_| serde_json::from_str::<TransactionError>(&resp).unwrap())
+        parse_response(&resp)
     }
 
     pub fn transaction_details(
         &self,
         intent_hash: &str,
     ) -> Result<TransactionDetails, TransactionError> {
-        let mut map = HashMap::new();
-        map.insert("intent_hash", intent_hash);
+        self.transaction_details_with_opt_ins(intent_hash, TransactionDetailsOptIns::default())
+    }
 
-        let resp = self
-            .client
-            .post(self.url.clone() + "/transaction/committed-details")
-            .header(ACCEPT, "application/json")
-            .header(CONTENT_TYPE, "application/json")
-            .json(&map)
-            .send()
-            .unwrap()
-            .text()
-            .unwrap();
+    pub fn transaction_details_with_opt_ins(
+        &self,
+        intent_hash: &str,
+        opt_ins: TransactionDetailsOptIns,
+    ) -> Result<TransactionDetails, TransactionError> {
+        let request = TransactionDetailsRequest {
+            intent_hash: intent_hash.to_string(),
+            opt_ins,
+        };
+
+        let (resp, _) = self.post("/transaction/committed-details", Some(&request))?;
+
+        parse_response(&resp)
+    }
+
+    // Poll the transaction status until it is no longer pending,
+    // then get the transaction details.
+    pub fn wait_for_transaction(
+        &self,
+        intent_hash: &str,
+    ) -> Result<TransactionDetails, TransactionError> {
+        loop {
+            let status = self.transaction_status(intent_hash)?;
+            if !status.status.eq("Pending") {
+                break;
+            }
+            thread::sleep(time::Duration::from_millis(1000));
+        }
+
+        self.transaction_details(intent_hash)
+    }
+
+    pub fn stream_transactions(
+        &self,
+        request: &StreamTransactionsRequest,
+    ) -> Result<StreamTransactions, TransactionError> {
+        let (resp, _) = self.post("/stream/transactions", Some(request))?;
+
+        parse_response(&resp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn retry_config() -> RetryConfig {
+        RetryConfig {
+            max_retries: 3,
+            initial_backoff: time::Duration::from_millis(100),
+            max_backoff: time::Duration::from_secs(1),
+        }
+    }
+
+    // Backoff with jitter is between a half and the full delay
+    fn assert_backoff_within(backoff: time::Duration, full_ms: u64) {
+        let backoff = backoff.as_millis() as u64;
+        assert!(
+            (full_ms / 2..=full_ms).contains(&backoff),
+            "backoff {} ms not within {}..={} ms",
+            backoff,
+            full_ms / 2,
+            full_ms
+        );
+    }
+
+    #[test]
+    fn backoff_grows_exponentially() {
+        let config = retry_config();
+        for _ in 0..100 {
+            assert_backoff_within(config.backoff(0, None), 100);
+            assert_backoff_within(config.backoff(1, None), 200);
+            assert_backoff_within(config.backoff(2, None), 400);
+            assert_backoff_within(config.backoff(3, None), 800);
+        }
+    }
+
+    #[test]
+    fn backoff_is_clamped_to_max() {
+        let config = retry_config();
+        for retry in [4, 10, 31, 32, u32::MAX] {
+            assert_backoff_within(config.backoff(retry, None), 1000);
+        }
+    }
+
+    #[test]
+    fn backoff_jitter_varies() {
+        let config = retry_config();
+        let backoffs: HashSet<_> = (0..100).map(|_| config.backoff(3, None)).collect();
+        assert!(backoffs.len() > 1);
+    }
+
+    #[test]
+    fn backoff_follows_retry_after_up_to_max() {
+        let config = retry_config();
+        assert_eq!(
+            config.backoff(0, Some(time::Duration::from_millis(300))),
+            time::Duration::from_millis(300)
+        );
+        assert_eq!(
+            config.backoff(2, Some(time::Duration::from_secs(86400))),
+            time::Duration::from_secs(1)
+        );
+    }
+
+    fn retry_after_headers(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn retry_after_in_seconds() {
+        assert_eq!(
+            parse_retry_after(&retry_after_headers("120")),
+            Some(time::Duration::from_secs(120))
+        );
+        assert_eq!(
+            parse_retry_after(&retry_after_headers(" 0 ")),
+            Some(time::Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn retry_after_http_date_and_invalid_ignored() {
+        assert_eq!(
+            parse_retry_after(&retry_after_headers("Wed, 21 Oct 2015 07:28:00 GMT")),
+            None
+        );
+        assert_eq!(parse_retry_after(&retry_after_headers("-1")), None);
+        assert_eq!(parse_retry_after(&retry_after_headers("1.5")), None);
+        assert_eq!(parse_retry_after(&HeaderMap::new()), None);
+    }
 
-        serde_json::from_str::<TransactionDetails>(&resp)
-            .map_err(|_| serde_json::from_str::<TransactionError>(&resp).unwrap())
+    #[test]
+    fn retryable_statuses() {
+        assert!(is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable_status(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(is_retryable_status(StatusCode::BAD_GATEWAY));
+        assert!(is_retryable_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!is_retryable_status(StatusCode::OK));
+        assert!(!is_retryable_status(StatusCode::BAD_REQUEST));
+        assert!(!is_retryable_status(StatusCode::NOT_FOUND));
     }
 }
@@ -0,0 +1,407 @@
+//! EIP-2335 keystores, ie. JSON files with the BLS secret key encrypted with a password.
+
+use crate::bls::BlsSecretKey;
+use aes::cipher::{KeyIvInit, StreamCipher};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use unicode_normalization::UnicodeNormalization;
+
+type Aes128Ctr = ctr::Ctr128BE<aes::Aes128>;
+
+// Scrypt parameters of the newly created keystores, the same as Ethereum tooling uses.
+// Tests use cheaper n, it is recorded in the keystore anyway.
+#[cfg(not(test))]
+const SCRYPT_LOG_N: u8 = 18;
+#[cfg(test)]
+const SCRYPT_LOG_N: u8 = 10;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+const DECRYPTION_KEY_LENGTH: usize = 32;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct KeystoreModule {
+    pub function: String,
+    /// Function specific parameters
+    pub params: serde_json::Value,
+    /// Hex-encoded function output
+    pub message: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct KeystoreCrypto {
+    pub kdf: KeystoreModule,
+    pub checksum: KeystoreModule,
+    pub cipher: KeystoreModule,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Keystore {
+    pub crypto: KeystoreCrypto,
+    #[serde(default)]
+    pub description: String,
+    /// Hex-encoded public key, may be empty
+    #[serde(default)]
+    pub pubkey: String,
+    /// EIP-2334 derivation path of the key, may be empty
+    pub path: String,
+    pub uuid: String,
+    pub version: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct ScryptParams {
+    dklen: usize,
+    n: u32,
+    r: u32,
+    p: u32,
+    salt: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Pbkdf2Params {
+    dklen: usize,
+    c: u32,
+    prf: String,
+    salt: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CipherParams {
+    iv: String,
+}
+
+impl Keystore {
+    pub fn load(file: &str) -> Result<Self, String> {
+        let json = fs::read_to_string(file)
+            .map_err(|err| format!("Cannot read keystore {}: {}", file, err))?;
+        serde_json::from_str(&json).map_err(|err| format!("Invalid keystore {}: {}", file, err))
+    }
+
+    pub fn save(&self, file: &str) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).unwrap();
+        fs::write(file, json).map_err(|err| format!("Cannot write keystore {}: {}", file, err))
+    }
+
+    /// Encrypt the secret key with the password, using scrypt and AES-128-CTR.
+    pub fn encrypt(secret_key: &BlsSecretKey, password: &str, path: &str) -> Result<Self, String> {
+        let mut salt = [0u8; 32];
+        let mut iv = [0u8; 16];
+        let mut uuid = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut salt);
+        rand::thread_rng().fill_bytes(&mut iv);
+        rand::thread_rng().fill_bytes(&mut uuid);
+
+        let mut decryption_key = [0u8; DECRYPTION_KEY_LENGTH];
+        let params = scrypt::Params::new(SCRYPT_LOG_N, SCRYPT_R, SCRYPT_P, DECRYPTION_KEY_LENGTH)
+            .map_err(|err| format!("Invalid scrypt parameters: {}", err))?;
+        scrypt::scrypt(
+            normalize_password(password).as_bytes(),
+            &salt,
+            &params,
+            &mut decryption_key,
+        )
+        .map_err(|err| format!("Scrypt failed: {}", err))?;
+
+        let mut cipher_message = secret_key.to_bytes().to_vec();
+        Aes128Ctr::new(decryption_key[..16].into(), iv[..].into())
+            .apply_keystream(&mut cipher_message);
+
+        Ok(Self {
+            crypto: KeystoreCrypto {
+                kdf: KeystoreModule {
+                    function: "scrypt".to_string(),
+                    params: serde_json::json!({
+                        "dklen": DECRYPTION_KEY_LENGTH,
+                        "n": 1u32 << SCRYPT_LOG_N,
+                        "r": SCRYPT_R,
+                        "p": SCRYPT_P,
+                        "salt": hex::encode(salt),
+                    }),
+                    message: String::new(),
+                },
+                checksum: KeystoreModule {
+                    function: "sha256".to_string(),
+                    params: serde_json::json!({}),
+                    message: hex::encode(checksum(&decryption_key, &cipher_message)),
+                },
+                cipher: KeystoreModule {
+                    function: "aes-128-ctr".to_string(),
+                    params: serde_json::json!({ "iv": hex::encode(iv) }),
+                    message: hex::encode(cipher_message),
+                },
+            },
+            description: String::new(),
+            pubkey: hex::encode(secret_key.public_key().0),
+            path: path.to_string(),
+            uuid: format_uuid_v4(uuid),
+            version: 4,
+        })
+    }
+
+    /// Decrypt the secret key with the password.
+    /// Fails if the password is wrong or the key does not match the keystore public key.
+    pub fn decrypt(&self, password: &str) -> Result<BlsSecretKey, String> {
+        if self.version != 4 {
+            return Err(format!("Unsupported keystore version {}", self.version));
+        }
+        let decryption_key = self.decryption_key(&normalize_password(password))?;
+        if decryption_key.len() < 32 {
+            return Err("Decryption key shorter than 32 bytes".to_string());
+        }
+
+        let crypto = &self.crypto;
+        let cipher_message = decode_hex("cipher message", &crypto.cipher.message)?;
+        if crypto.checksum.function != "sha256" {
+            return Err(format!(
+                "Unsupported checksum function '{}'",
+                crypto.checksum.function
+            ));
+        }
+        let expected_checksum = decode_hex("checksum", &crypto.checksum.message)?;
+        if checksum(&decryption_key, &cipher_message)[..] != expected_checksum[..] {
+            return Err("Invalid password, checksum does not match".to_string());
+        }
+
+        if crypto.cipher.function != "aes-128-ctr" {
+            return Err(format!(
+                "Unsupported cipher function '{}'",
+                crypto.cipher.function
+            ));
+        }
+        let params: CipherParams = parse_params(&crypto.cipher)?;
+        let iv = decode_hex("cipher iv", &params.iv)?;
+        if iv.len() != 16 {
+            return Err(format!("Cipher iv must be 16 bytes, got {}", iv.len()));
+        }
+        let mut secret = cipher_message;
+        Aes128Ctr::new(decryption_key[..16].into(), iv[..].into()).apply_keystream(&mut secret);
+
+        let secret_key = BlsSecretKey::from_bytes(&secret)?;
+        if !self.pubkey.is_empty()
+            && decode_hex("pubkey", &self.pubkey)?[..] != secret_key.public_key().0[..]
+        {
+            return Err("Decrypted key does not match the keystore public key".to_string());
+        }
+        Ok(secret_key)
+    }
+
+    fn decryption_key(&self, password: &str) -> Result<Vec<u8>, String> {
+        let kdf = &self.crypto.kdf;
+        match kdf.function.as_str() {
+            "scrypt" => {
+                let params: ScryptParams = parse_params(kdf)?;
+                if !params.n.is_power_of_two() {
+                    return Err(format!("Scrypt n must be a power of two, got {}", params.n));
+                }
+                let salt = decode_hex("kdf salt", &params.salt)?;
+                let scrypt_params = scrypt::Params::new(
+                    params.n.trailing_zeros() as u8,
+                    params.r,
+                    params.p,
+                    params.dklen,
+                )
+                .map_err(|err| format!("Invalid scrypt parameters: {}", err))?;
+                let mut key = vec![0u8; params.dklen];
+                scrypt::scrypt(password.as_bytes(), &salt, &scrypt_params, &mut key)
+                    .map_err(|err| format!("Scrypt failed: {}", err))?;
+                Ok(key)
+            }
+            "pbkdf2" => {
+                let params: Pbkdf2Params = parse_params(kdf)?;
+                if params.prf != "hmac-sha256" {
+                    return Err(format!("Unsupported pbkdf2 prf '{}'", params.prf));
+                }
+                let salt = decode_hex("kdf salt", &params.salt)?;
+                let mut key = vec![0u8; params.dklen];
+                pbkdf2::pbkdf2_hmac::<Sha256>(password.as_bytes(), &salt, params.c, &mut key);
+                Ok(key)
+            }
+            function => Err(format!("Unsupported kdf function '{}'", function)),
+        }
+    }
+}
+
+// Password is NFKD normalized with control codes stripped, as per EIP-2335
+fn normalize_password(password: &str) -> String {
+    password.nfkd().filter(|c| !c.is_control()).collect()
+}
+
+fn checksum(decryption_key: &[u8], cipher_message: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(&decryption_key[16..32]);
+    hasher.update(cipher_message);
+    hasher.finalize().into()
+}
+
+fn parse_params<T: serde::de::DeserializeOwned>(module: &KeystoreModule) -> Result<T, String> {
+    serde_json::from_value(module.params.clone())
+        .map_err(|err| format!("Invalid {} parameters: {}", module.function, err))
+}
+
+fn decode_hex(name: &str, value: &str) -> Result<Vec<u8>, String> {
+    hex::decode(value).map_err(|err| format!("Invalid {} hex: {}", name, err))
+}
+
+fn format_uuid_v4(mut bytes: [u8; 16]) -> String {
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = hex::encode(bytes);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // EIP-2335 test vectors, the password has a control code added, which is stripped
+    const PASSWORD: &str = "𝔱𝔢𝔰𝔱𝔭𝔞𝔰𝔰𝔴𝔬𝔯𝔡\u{7f}🔑";
+    const SECRET: &str = "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f";
+    const PUBKEY: &str = "9612d7a727c9d0a22e185a1c768478dfe919cada9266988cb32359c11f2b7b27f4ae4040902382ae2910c15e2b420d07";
+
+    const SCRYPT_KEYSTORE: &str = r#"{
+        "crypto": {
+            "kdf": {
+                "function": "scrypt",
+                "params": {
+                    "dklen": 32,
+                    "n": 262144,
+                    "p": 1,
+                    "r": 8,
+                    "salt": "d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"
+                },
+                "message": ""
+            },
+            "checksum": {
+                "function": "sha256",
+                "params": {},
+                "message": "d2217fe5f3e9a1e34581ef8a78f7c9928e436d36dacc5e846690a5581e8ea484"
+            },
+            "cipher": {
+                "function": "aes-128-ctr",
+                "params": {
+                    "iv": "264daa3f303d7259501c93d997d84fe6"
+                },
+                "message": "06ae90d55fe0a6e9c5c3bc5b170827b2e5cce3929ed3f116c2811e6366dfe20f"
+            }
+        },
+        "description": "This is a test keystore that uses scrypt to secure the secret.",
+        "pubkey": "9612d7a727c9d0a22e185a1c768478dfe919cada9266988cb32359c11f2b7b27f4ae4040902382ae2910c15e2b420d07",
+        "path": "m/12381/60/3141592653/589793238",
+        "uuid": "1d85ae20-35c5-4611-98e8-aa14a633906f",
+        "version": 4
+    }"#;
+
+    const PBKDF2_KEYSTORE: &str = r#"{
+        "crypto": {
+            "kdf": {
+                "function": "pbkdf2",
+                "params": {
+                    "dklen": 32,
+                    "c": 262144,
+                    "prf": "hmac-sha256",
+                    "salt": "d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"
+                },
+                "message": ""
+            },
+            "checksum": {
+                "function": "sha256",
+                "params": {},
+                "message": "8a9f5d9912ed7e75ea794bc5a89bca5f193721d30868ade6f73043c6ea6febf1"
+            },
+            "cipher": {
+                "function": "aes-128-ctr",
+                "params": {
+                    "iv": "264daa3f303d7259501c93d997d84fe6"
+                },
+                "message": "cee03fde2af33149775b7223e7845e4fb2c8ae1792e5f99fe9ecf474cc8c16ad"
+            }
+        },
+        "description": "This is a test keystore that uses PBKDF2 to secure the secret.",
+        "pubkey": "9612d7a727c9d0a22e185a1c768478dfe919cada9266988cb32359c11f2b7b27f4ae4040902382ae2910c15e2b420d07",
+        "path": "m/12381/60/0/0",
+        "uuid": "64625def-3331-4eea-ab6f-782f3ed16a83",
+        "version": 4
+    }"#;
+
+    fn secret_key() -> BlsSecretKey {
+        BlsSecretKey::from_bytes(&hex::decode(SECRET).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn normalize_password_strips_control_codes() {
+        assert_eq!(
+            hex::encode(normalize_password(PASSWORD)),
+            "7465737470617373776f7264f09f9491"
+        );
+    }
+
+    #[test]
+    fn decrypt_scrypt_spec_vector() {
+        let keystore: Keystore = serde_json::from_str(SCRYPT_KEYSTORE).unwrap();
+        let secret_key = keystore.decrypt(PASSWORD).unwrap();
+        assert_eq!(hex::encode(secret_key.to_bytes()), SECRET);
+        assert_eq!(hex::encode(secret_key.public_key().0), PUBKEY);
+    }
+
+    #[test]
+    fn decrypt_pbkdf2_spec_vector() {
+        let keystore: Keystore = serde_json::from_str(PBKDF2_KEYSTORE).unwrap();
+        let secret_key = keystore.decrypt(PASSWORD).unwrap();
+        assert_eq!(hex::encode(secret_key.to_bytes()), SECRET);
+    }
+
+    #[test]
+    fn encrypt_decrypt_round_trip() {
+        let keystore = Keystore::encrypt(&secret_key(), PASSWORD, "m/12381/3600/0/0/0").unwrap();
+        assert_eq!(keystore.pubkey, PUBKEY);
+        assert_eq!(keystore.path, "m/12381/3600/0/0/0");
+        assert_eq!(keystore.version, 4);
+
+        // Survives serialization, and the password matches in any normalization form
+        let keystore: Keystore =
+            serde_json::from_str(&serde_json::to_string(&keystore).unwrap()).unwrap();
+        let secret_key = keystore.decrypt("testpassword🔑").unwrap();
+        assert_eq!(hex::encode(secret_key.to_bytes()), SECRET);
+    }
+
+    #[test]
+    fn decrypt_wrong_password() {
+        let keystore = Keystore::encrypt(&secret_key(), PASSWORD, "").unwrap();
+        let err = keystore.decrypt("wrong password").err().unwrap();
+        assert!(err.contains("checksum does not match"), "{}", err);
+    }
+
+    #[test]
+    fn decrypt_pubkey_mismatch() {
+        let mut keystore = Keystore::encrypt(&secret_key(), PASSWORD, "").unwrap();
+        let other = BlsSecretKey::from_u64(1).unwrap();
+        keystore.pubkey = hex::encode(other.public_key().0);
+        let err = keystore.decrypt(PASSWORD).err().unwrap();
+        assert!(
+            err.contains("does not match the keystore public key"),
+            "{}",
+            err
+        );
+
+        // Public key is optional
+        keystore.pubkey = String::new();
+        assert!(keystore.decrypt(PASSWORD).is_ok());
+    }
+
+    #[test]
+    fn uuid_v4_format() {
+        let uuid = format_uuid_v4([0xff; 16]);
+        assert_eq!(uuid, "ffffffff-ffff-4fff-bfff-ffffffffffff");
+        let uuid = format_uuid_v4([0; 16]);
+        assert_eq!(uuid, "00000000-0000-4000-8000-000000000000");
+    }
+}
@@ -0,0 +1,28 @@
+//! Library to work with the CryptoScrypto blueprint via the Gateway API.
+//!
+//! It provides:
+//! - blocking (and optionally async) Gateway API client
+//! - helpers to build and notarize transactions
+//! - typed client of the CryptoScrypto package
+//! - off-ledger BLS operations, eg. proofs of possession and threshold signatures
+
+#[cfg(feature = "async")]
+pub mod async_gateway;
+pub mod bls;
+pub mod client;
+pub mod compat;
+pub mod crypto_scrypto;
+pub mod edge_cases;
+pub mod error;
+pub mod gateway;
+pub mod keystore;
+pub mod load;
+pub mod threshold;
+pub mod utils;
+
+#[cfg(feature = "async")]
+pub use async_gateway::AsyncGatewayApiClient;
+pub use client::CryptoScryptoClient;
+pub use error::Error;
+pub use gateway::GatewayApiClient;
+pub use utils::create_notarized_transaction;
@@ -0,0 +1,276 @@
+use crate::gateway::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use transaction::prelude::*;
+
+// Statuses of the transaction, which is not finalized yet
+const IN_FLIGHT_STATUSES: [&str; 2] = ["Pending", "Unknown"];
+
+/// Outcome of a single load test transaction.
+#[derive(Clone, Debug)]
+pub struct LoadResult {
+    pub intent_hash: String,
+    /// Distinct statuses in the order they were observed
+    pub status_lifecycle: Vec<String>,
+    pub final_status: LoadStatus,
+    /// Time from the submission until the final status was observed
+    pub latency: Duration,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LoadStatus {
+    CommittedSuccess,
+    CommittedFailure,
+    Rejected(String),
+    /// Status could not be queried from the gateway
+    StatusError(String),
+    Duplicate,
+    TimedOut,
+}
+
+/// Summary of the load test run.
+#[derive(Clone, Debug)]
+pub struct LoadReport {
+    pub results: Vec<LoadResult>,
+    pub elapsed: Duration,
+}
+
+impl LoadReport {
+    pub fn count(&self, f: impl Fn(&LoadStatus) -> bool) -> usize {
+        self.results.iter().filter(|r| f(&r.final_status)).count()
+    }
+
+    pub fn committed(&self) -> usize {
+        self.count(|s| {
+            matches!(
+                s,
+                LoadStatus::CommittedSuccess | LoadStatus::CommittedFailure
+            )
+        })
+    }
+
+    /// Committed transactions per second, zero if no time elapsed
+    pub fn throughput(&self) -> f64 {
+        let elapsed = self.elapsed.as_secs_f64();
+        if elapsed == 0.0 {
+            return 0.0;
+        }
+        self.committed() as f64 / elapsed
+    }
+
+    /// Commit latency percentile (0-100) over committed transactions, using nearest-rank
+    pub fn latency_percentile(&self, percentile: f64) -> Option<Duration> {
+        let mut latencies: Vec<Duration> = self
+            .results
+            .iter()
+            .filter(|r| {
+                matches!(
+                    r.final_status,
+                    LoadStatus::CommittedSuccess | LoadStatus::CommittedFailure
+                )
+            })
+            .map(|r| r.latency)
+            .collect();
+        if latencies.is_empty() {
+            return None;
+        }
+        latencies.sort();
+        let rank = (percentile / 100.0 * latencies.len() as f64).ceil() as usize;
+        Some(latencies[rank.clamp(1, latencies.len()) - 1])
+    }
+}
+
+/// Submit given transactions using `concurrency` worker threads.
+/// Each worker submits a transaction and polls its status until it is finalized
+/// or the timeout elapses, then it takes the next one.
+pub fn run_load(
+    gateway: &GatewayApiClient,
+    transactions: Vec<(NotarizedTransactionV1, String)>,
+    concurrency: usize,
+    poll_interval: Duration,
+    timeout: Duration,
+) -> LoadReport {
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(vec![]);
+    let start = Instant::now();
+
+    thread::scope(|scope| {
+        for _ in 0..concurrency.max(1) {
+            scope.spawn(|| loop {
+                let idx = next.fetch_add(1, Ordering::SeqCst);
+                let Some((transaction, intent_hash)) = transactions.get(idx) else {
+                    break;
+                };
+                let result = submit_and_track(
+                    gateway,
+                    transaction.clone(),
+                    intent_hash,
+                    poll_interval,
+                    timeout,
+                );
+                println!(
+                    "[{}] {} : {:?} ({} ms) {}",
+                    idx,
+                    result.intent_hash,
+                    result.final_status,
+                    result.latency.as_millis(),
+                    result.status_lifecycle.join(" -> ")
+                );
+                results.lock().unwrap().push(result);
+            });
+        }
+    });
+
+    LoadReport {
+        results: results.into_inner().unwrap(),
+        elapsed: start.elapsed(),
+    }
+}
+
+fn submit_and_track(
+    gateway: &GatewayApiClient,
+    transaction: NotarizedTransactionV1,
+    intent_hash: &str,
+    poll_interval: Duration,
+    timeout: Duration,
+) -> LoadResult {
+    let submitted_at = Instant::now();
+    let mut result = LoadResult {
+        intent_hash: intent_hash.to_string(),
+        status_lifecycle: vec![],
+        final_status: LoadStatus::TimedOut,
+        latency: Duration::ZERO,
+    };
+
+    match gateway.transaction_submit(transaction) {
+        Ok(submit) if submit.duplicate => {
+            result.final_status = LoadStatus::Duplicate;
+            result.latency = submitted_at.elapsed();
+            return result;
+        }
+        Ok(_) => {}
+        Err(err) => {
+            result.final_status = LoadStatus::Rejected(err.message);
+            result.latency = submitted_at.elapsed();
+            return result;
+        }
+    }
+
+    while submitted_at.elapsed() < timeout {
+        match gateway.transaction_status(intent_hash) {
+            Ok(status) => {
+                if result.status_lifecycle.last() != Some(&status.intent_status) {
+                    result.status_lifecycle.push(status.intent_status.clone());
+                }
+                if !IN_FLIGHT_STATUSES.contains(&status.status.as_str()) {
+                    result.final_status = match status.status.as_str() {
+                        "CommittedSuccess" => LoadStatus::CommittedSuccess,
+                        "CommittedFailure" => LoadStatus::CommittedFailure,
+                        _ => LoadStatus::Rejected(
+                            status
+                                .error_message
+                                .unwrap_or(status.intent_status_description),
+                        ),
+                    };
+                    break;
+                }
+            }
+            Err(err) => {
+                result.final_status = LoadStatus::StatusError(err.message);
+                break;
+            }
+        }
+        thread::sleep(poll_interval);
+    }
+
+    result.latency = submitted_at.elapsed();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load_report(latencies_ms: &[u64], elapsed: Duration) -> LoadReport {
+        let results = latencies_ms
+            .iter()
+            .map(|ms| LoadResult {
+                intent_hash: String::new(),
+                status_lifecycle: vec![],
+                final_status: LoadStatus::CommittedSuccess,
+                latency: Duration::from_millis(*ms),
+            })
+            .collect();
+        LoadReport { results, elapsed }
+    }
+
+    #[test]
+    fn latency_percentile_single_sample() {
+        let report = load_report(&[42], Duration::from_secs(1));
+        for percentile in [50.0, 95.0, 99.0] {
+            assert_eq!(
+                report.latency_percentile(percentile),
+                Some(Duration::from_millis(42))
+            );
+        }
+    }
+
+    #[test]
+    fn latency_percentile_nearest_rank() {
+        // 1..=100 ms in reverse order, so that sorting is exercised
+        let latencies: Vec<u64> = (1..=100).rev().collect();
+        let report = load_report(&latencies, Duration::from_secs(1));
+        assert_eq!(
+            report.latency_percentile(50.0),
+            Some(Duration::from_millis(50))
+        );
+        assert_eq!(
+            report.latency_percentile(95.0),
+            Some(Duration::from_millis(95))
+        );
+        assert_eq!(
+            report.latency_percentile(99.0),
+            Some(Duration::from_millis(99))
+        );
+        assert_eq!(
+            report.latency_percentile(0.0),
+            Some(Duration::from_millis(1))
+        );
+
+        let report = load_report(&[10, 20, 30, 40, 50], Duration::from_secs(1));
+        assert_eq!(
+            report.latency_percentile(50.0),
+            Some(Duration::from_millis(30))
+        );
+        assert_eq!(
+            report.latency_percentile(95.0),
+            Some(Duration::from_millis(50))
+        );
+    }
+
+    #[test]
+    fn latency_percentile_ignores_uncommitted() {
+        let mut report = load_report(&[], Duration::from_secs(1));
+        assert_eq!(report.latency_percentile(50.0), None);
+
+        report.results.push(LoadResult {
+            intent_hash: String::new(),
+            status_lifecycle: vec![],
+            final_status: LoadStatus::StatusError("timeout".to_string()),
+            latency: Duration::from_millis(5),
+        });
+        assert_eq!(report.latency_percentile(50.0), None);
+    }
+
+    #[test]
+    fn throughput_of_committed_transactions() {
+        assert_eq!(
+            load_report(&[1, 2, 3, 4], Duration::from_secs(2)).throughput(),
+            2.0
+        );
+        assert_eq!(load_report(&[1, 2], Duration::ZERO).throughput(), 0.0);
+        assert_eq!(load_report(&[], Duration::ZERO).throughput(), 0.0);
+    }
+}
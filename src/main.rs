@@ -1,7 +1,4 @@
-//mod error;
 mod cli;
-mod gateway;
-mod utils;
 
 fn main() {
     cli::run()
@@ -0,0 +1,412 @@
+//! Threshold (t-of-n) BLS signatures.
+//!
+//! The secret key is split into n shares with Shamir's secret sharing, any t partial
+//! signatures made with the shares combine (Lagrange interpolation at 0) into the signature
+//! of the secret key, which verifies with its public key as any other BLS signature.
+
+use crate::bls::{parse_signature, BlsSecretKey};
+use crate::keystore::Keystore;
+use blst::{
+    blst_bendian_from_scalar, blst_fr, blst_fr_add, blst_fr_from_scalar, blst_fr_from_uint64,
+    blst_fr_inverse, blst_fr_mul, blst_fr_sub, blst_lendian_from_scalar, blst_p2,
+    blst_p2_add_or_double, blst_p2_affine, blst_p2_affine_in_g2, blst_p2_compress,
+    blst_p2_from_affine, blst_p2_mult, blst_p2_uncompress, blst_scalar, blst_scalar_from_bendian,
+    blst_scalar_from_fr, BLST_ERROR,
+};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+use transaction::prelude::*;
+
+/// Share of the secret key, ie. the Shamir polynomial evaluated at `index`.
+pub struct KeyShare {
+    /// Non-zero x coordinate of the share
+    pub index: u64,
+    pub secret_key: BlsSecretKey,
+}
+
+// Keystore description of the key share, which carries its index
+const KEYSTORE_DESCRIPTION_PREFIX: &str = "Threshold key share ";
+
+impl KeyShare {
+    /// Encrypt the share into EIP-2335 keystore, its index is kept in the description.
+    pub fn to_keystore(&self, password: &str) -> Result<Keystore, String> {
+        let mut keystore = Keystore::encrypt(&self.secret_key, password, "")?;
+        keystore.description = format!("{}{}", KEYSTORE_DESCRIPTION_PREFIX, self.index);
+        Ok(keystore)
+    }
+
+    /// Decrypt the share from the keystore written by [`KeyShare::to_keystore`].
+    pub fn from_keystore(keystore: &Keystore, password: &str) -> Result<Self, String> {
+        let index = keystore
+            .description
+            .strip_prefix(KEYSTORE_DESCRIPTION_PREFIX)
+            .and_then(|index| index.parse().ok())
+            .ok_or_else(|| {
+                format!(
+                    "Keystore '{}' is not a threshold key share",
+                    keystore.description
+                )
+            })?;
+        Ok(Self {
+            index,
+            secret_key: keystore.decrypt(password)?,
+        })
+    }
+}
+
+/// Signature made with the key share of the given index.
+#[derive(Clone, Copy, Debug)]
+pub struct PartialSignature {
+    pub index: u64,
+    pub signature: Bls12381G2Signature,
+}
+
+/// Split the secret key into `shares` key shares, any `threshold` of which can sign.
+/// Polynomial coefficients are derived from the secret key (like deterministic nonces),
+/// so splitting is reproducible, while the shares reveal nothing without the secret key.
+pub fn split_secret_key(
+    secret_key: &BlsSecretKey,
+    threshold: usize,
+    shares: usize,
+) -> Result<Vec<KeyShare>, String> {
+    if threshold == 0 || threshold > shares {
+        return Err(format!(
+            "Threshold {} must be in range 1..={}",
+            threshold, shares
+        ));
+    }
+    let secret_key_bytes = secret_key.to_bytes();
+    let mut coefficients = vec![fr_from_bytes(&secret_key_bytes)];
+    for i in 1..threshold {
+        let key_info = format!("threshold coefficient {}", i);
+        let coefficient = BlsSecretKey::from_ikm(&secret_key_bytes, key_info.as_bytes())?;
+        coefficients.push(fr_from_bytes(&coefficient.to_bytes()));
+    }
+
+    (1..=shares as u64)
+        .map(|index| {
+            // Horner's method
+            let x = fr_from_u64(index);
+            let y = coefficients
+                .iter()
+                .rev()
+                .fold(blst_fr::default(), |acc, coefficient| {
+                    fr_add(&fr_mul(&acc, &x), coefficient)
+                });
+            Ok(KeyShare {
+                index,
+                secret_key: BlsSecretKey::from_bytes(&fr_to_bytes(&y))?,
+            })
+        })
+        .collect()
+}
+
+/// Combine partial signatures of at least threshold distinct shares into the signature
+/// of the secret key.
+pub fn combine_partial_signatures(
+    partials: &[PartialSignature],
+) -> Result<Bls12381G2Signature, String> {
+    if partials.is_empty() {
+        return Err("No partial signatures to combine".to_string());
+    }
+    let indices: BTreeSet<u64> = partials.iter().map(|partial| partial.index).collect();
+    if indices.len() != partials.len() || indices.contains(&0) {
+        return Err("Partial signature indices must be distinct and non-zero".to_string());
+    }
+
+    let mut combined = blst_p2::default();
+    for partial in partials {
+        let point = decompress_g2(&partial.signature)?;
+        let lambda = fr_to_le_bytes(&lagrange_coefficient(partial.index, &indices));
+        let mut term = blst_p2::default();
+        let sum = combined;
+        unsafe {
+            blst_p2_mult(&mut term, &point, lambda.as_ptr(), 255);
+            blst_p2_add_or_double(&mut combined, &sum, &term);
+        }
+    }
+
+    let mut bytes = [0u8; Bls12381G2Signature::LENGTH];
+    unsafe {
+        blst_p2_compress(bytes.as_mut_ptr(), &combined);
+    }
+    Ok(Bls12381G2Signature(bytes))
+}
+
+// Lagrange basis polynomial of the index evaluated at 0: prod(x_j / (x_j - x_i)) for j != i
+fn lagrange_coefficient(index: u64, indices: &BTreeSet<u64>) -> blst_fr {
+    let x_i = fr_from_u64(index);
+    let mut numerator = fr_from_u64(1);
+    let mut denominator = fr_from_u64(1);
+    for &other in indices.iter().filter(|&&other| other != index) {
+        let x_j = fr_from_u64(other);
+        numerator = fr_mul(&numerator, &x_j);
+        denominator = fr_mul(&denominator, &fr_sub(&x_j, &x_i));
+    }
+    let mut inverse = blst_fr::default();
+    unsafe {
+        blst_fr_inverse(&mut inverse, &denominator);
+    }
+    fr_mul(&numerator, &inverse)
+}
+
+fn decompress_g2(signature: &Bls12381G2Signature) -> Result<blst_p2, String> {
+    let mut affine = blst_p2_affine::default();
+    let mut point = blst_p2::default();
+    unsafe {
+        match blst_p2_uncompress(&mut affine, signature.0.as_ptr()) {
+            BLST_ERROR::BLST_SUCCESS if blst_p2_affine_in_g2(&affine) => {
+                blst_p2_from_affine(&mut point, &affine)
+            }
+            BLST_ERROR::BLST_SUCCESS => {
+                return Err(format!("Signature {} not in G2 subgroup", signature))
+            }
+            err => return Err(format!("Invalid signature {}: {:?}", signature, err)),
+        }
+    }
+    Ok(point)
+}
+
+fn fr_from_u64(value: u64) -> blst_fr {
+    let limbs = [value, 0, 0, 0];
+    let mut fr = blst_fr::default();
+    unsafe {
+        blst_fr_from_uint64(&mut fr, limbs.as_ptr());
+    }
+    fr
+}
+
+fn fr_from_bytes(bytes: &[u8; 32]) -> blst_fr {
+    let mut scalar = blst_scalar::default();
+    let mut fr = blst_fr::default();
+    unsafe {
+        blst_scalar_from_bendian(&mut scalar, bytes.as_ptr());
+        blst_fr_from_scalar(&mut fr, &scalar);
+    }
+    fr
+}
+
+fn fr_to_scalar(fr: &blst_fr) -> blst_scalar {
+    let mut scalar = blst_scalar::default();
+    unsafe {
+        blst_scalar_from_fr(&mut scalar, fr);
+    }
+    scalar
+}
+
+fn fr_to_bytes(fr: &blst_fr) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    unsafe {
+        blst_bendian_from_scalar(bytes.as_mut_ptr(), &fr_to_scalar(fr));
+    }
+    bytes
+}
+
+fn fr_to_le_bytes(fr: &blst_fr) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    unsafe {
+        blst_lendian_from_scalar(bytes.as_mut_ptr(), &fr_to_scalar(fr));
+    }
+    bytes
+}
+
+fn fr_add(a: &blst_fr, b: &blst_fr) -> blst_fr {
+    let mut ret = blst_fr::default();
+    unsafe {
+        blst_fr_add(&mut ret, a, b);
+    }
+    ret
+}
+
+fn fr_sub(a: &blst_fr, b: &blst_fr) -> blst_fr {
+    let mut ret = blst_fr::default();
+    unsafe {
+        blst_fr_sub(&mut ret, a, b);
+    }
+    ret
+}
+
+fn fr_mul(a: &blst_fr, b: &blst_fr) -> blst_fr {
+    let mut ret = blst_fr::default();
+    unsafe {
+        blst_fr_mul(&mut ret, a, b);
+    }
+    ret
+}
+
+// Shares and partial signatures are written as "<index>:<hex>"
+fn parse_indexed(s: &str) -> Result<(u64, &str), String> {
+    let (index, value) = s
+        .split_once(':')
+        .ok_or_else(|| format!("'{}' must be given as '<index>:<hex>'", s))?;
+    let index = index
+        .parse()
+        .map_err(|err| format!("Invalid index '{}': {}", index, err))?;
+    Ok((index, value))
+}
+
+impl fmt::Display for KeyShare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}",
+            self.index,
+            hex::encode(self.secret_key.to_bytes())
+        )
+    }
+}
+
+impl FromStr for KeyShare {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (index, secret_key) = parse_indexed(s)?;
+        Ok(Self {
+            index,
+            secret_key: BlsSecretKey::from_str(secret_key)?,
+        })
+    }
+}
+
+impl fmt::Display for PartialSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.index, self.signature)
+    }
+}
+
+impl FromStr for PartialSignature {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (index, signature) = parse_indexed(s)?;
+        Ok(Self {
+            index,
+            signature: parse_signature(signature)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bls;
+
+    const MSG: &[u8] = b"Hello World!";
+
+    fn secret_key() -> BlsSecretKey {
+        BlsSecretKey::from_u64(42).unwrap()
+    }
+
+    fn partial_signatures(shares: &[KeyShare], indices: &[u64]) -> Vec<PartialSignature> {
+        indices
+            .iter()
+            .map(|&index| {
+                let share = shares.iter().find(|share| share.index == index).unwrap();
+                PartialSignature {
+                    index,
+                    signature: share.secret_key.sign(MSG),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn any_threshold_shares_give_the_signature() {
+        let secret_key = secret_key();
+        let expected = secret_key.sign(MSG);
+        let shares = split_secret_key(&secret_key, 3, 5).unwrap();
+        assert_eq!(shares.len(), 5);
+
+        for indices in [[1, 2, 3], [1, 3, 5], [5, 4, 2], [2, 3, 4], [3, 5, 1]] {
+            let signature =
+                combine_partial_signatures(&partial_signatures(&shares, &indices)).unwrap();
+            assert_eq!(signature, expected, "shares {:?}", indices);
+            assert!(bls::verify(MSG, &secret_key.public_key(), &signature));
+        }
+
+        // More than threshold shares give the same signature too
+        let signature =
+            combine_partial_signatures(&partial_signatures(&shares, &[1, 2, 3, 4, 5])).unwrap();
+        assert_eq!(signature, expected);
+    }
+
+    #[test]
+    fn fewer_than_threshold_shares_do_not_give_the_signature() {
+        let secret_key = secret_key();
+        let shares = split_secret_key(&secret_key, 3, 5).unwrap();
+        for indices in [[1, 2], [4, 5], [2, 5]] {
+            let signature =
+                combine_partial_signatures(&partial_signatures(&shares, &indices)).unwrap();
+            assert_ne!(signature, secret_key.sign(MSG), "shares {:?}", indices);
+            assert!(!bls::verify(MSG, &secret_key.public_key(), &signature));
+        }
+    }
+
+    #[test]
+    fn one_of_n_shares_equal_the_secret_key() {
+        let secret_key = secret_key();
+        for share in split_secret_key(&secret_key, 1, 3).unwrap() {
+            assert_eq!(share.secret_key.to_bytes(), secret_key.to_bytes());
+        }
+    }
+
+    #[test]
+    fn split_is_reproducible() {
+        let first = split_secret_key(&secret_key(), 2, 3).unwrap();
+        let second = split_secret_key(&secret_key(), 2, 3).unwrap();
+        for (a, b) in first.iter().zip(&second) {
+            assert_eq!(a.to_string(), b.to_string());
+        }
+    }
+
+    #[test]
+    fn split_invalid_threshold() {
+        assert!(split_secret_key(&secret_key(), 0, 3).is_err());
+        assert!(split_secret_key(&secret_key(), 4, 3).is_err());
+    }
+
+    #[test]
+    fn combine_invalid_indices() {
+        let shares = split_secret_key(&secret_key(), 2, 3).unwrap();
+        assert!(combine_partial_signatures(&[]).is_err());
+
+        let duplicate = partial_signatures(&shares, &[1, 1]);
+        assert!(combine_partial_signatures(&duplicate).is_err());
+
+        let mut zero = partial_signatures(&shares, &[1, 2]);
+        zero[1].index = 0;
+        assert!(combine_partial_signatures(&zero).is_err());
+    }
+
+    #[test]
+    fn key_share_keystore_round_trip() {
+        let shares = split_secret_key(&secret_key(), 2, 3).unwrap();
+        let keystore = shares[1].to_keystore("password").unwrap();
+        assert_eq!(keystore.description, "Threshold key share 2");
+
+        let share = KeyShare::from_keystore(&keystore, "password").unwrap();
+        assert_eq!(share.index, 2);
+        assert_eq!(share.secret_key.to_bytes(), shares[1].secret_key.to_bytes());
+
+        assert!(KeyShare::from_keystore(&keystore, "wrong").is_err());
+        let plain = Keystore::encrypt(&secret_key(), "password", "").unwrap();
+        assert!(KeyShare::from_keystore(&plain, "password").is_err());
+    }
+
+    #[test]
+    fn key_share_and_partial_signature_strings() {
+        let shares = split_secret_key(&secret_key(), 2, 3).unwrap();
+        let share = KeyShare::from_str(&shares[0].to_string()).unwrap();
+        assert_eq!(share.index, 1);
+        assert_eq!(share.secret_key.to_bytes(), shares[0].secret_key.to_bytes());
+
+        let partial = partial_signatures(&shares, &[3])[0];
+        let parsed = PartialSignature::from_str(&partial.to_string()).unwrap();
+        assert_eq!(parsed.index, 3);
+        assert_eq!(parsed.signature, partial.signature);
+
+        assert!(KeyShare::from_str("no index").is_err());
+        assert!(PartialSignature::from_str("x:00").is_err());
+    }
+}
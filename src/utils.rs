@@ -1,11 +1,27 @@
 use crate::gateway::*;
+use transaction::manifest::{compile, decompile, BlobProvider};
 use transaction::prelude::*;
 
+// Number of epochs the transaction remains valid for, starting from the current epoch
+pub const EPOCH_VALIDITY_RANGE: u64 = 10;
+
 pub fn create_notarized_transaction(
     network_definition: &NetworkDefinition,
     epoch: u64,
     private_key: &Secp256k1PrivateKey,
     manifest: TransactionManifestV1,
+) -> (NotarizedTransactionV1, IntentHash) {
+    create_notarized_transaction_with_nonce(network_definition, epoch, private_key, manifest, 5)
+}
+
+// Transactions with the same manifest, epoch and notary get distinct intent hashes
+// only if their nonces differ.
+pub fn create_notarized_transaction_with_nonce(
+    network_definition: &NetworkDefinition,
+    epoch: u64,
+    private_key: &Secp256k1PrivateKey,
+    manifest: TransactionManifestV1,
+    nonce: u32,
 ) -> (NotarizedTransactionV1, IntentHash) {
     let transaction = TransactionBuilder::new()
         .header(TransactionHeaderV1 {
@@ -14,8 +30,8 @@ pub fn create_notarized_transaction(
             // Eg. in production nonce mustn't be hardcoded.
             network_id: network_definition.id,
             start_epoch_inclusive: Epoch::of(epoch),
-            end_epoch_exclusive: Epoch::of(epoch + 10),
-            nonce: 5,
+            end_epoch_exclusive: Epoch::of(epoch + EPOCH_VALIDITY_RANGE),
+            nonce,
             notary_public_key: private_key.public_key().into(),
             notary_is_signatory: false,
             tip_percentage: 0,
@@ -44,3 +60,51 @@ pub fn transaction_output(details: TransactionDetails) -> Vec<u8> {
         panic!("Transaction error: {:?}", error);
     }
 }
+
+// Convert manifest instructions into human-readable text (.rtm format)
+pub fn decompile_manifest(
+    network_definition: &NetworkDefinition,
+    instructions: &[InstructionV1],
+) -> String {
+    decompile(instructions, network_definition).expect("Manifest decompilation failed")
+}
+
+// Compile manifest text (.rtm format) for the given network
+pub fn compile_manifest(
+    network_definition: &NetworkDefinition,
+    manifest: &str,
+    blobs: Vec<Vec<u8>>,
+) -> TransactionManifestV1 {
+    compile(
+        manifest,
+        network_definition,
+        BlobProvider::new_with_blobs(blobs),
+    )
+    .unwrap_or_else(|err| panic!("Manifest compilation error: {:?}", err))
+}
+
+// Check if any instruction locks the fee
+pub fn has_fee_lock(instructions: &[InstructionV1]) -> bool {
+    instructions.iter().any(|instruction| {
+        matches!(
+            instruction,
+            InstructionV1::CallMethod { method_name, .. }
+                if method_name == "lock_fee" || method_name == "lock_contingent_fee"
+                    || method_name == "lock_fee_and_withdraw"
+                    || method_name == "lock_fee_and_withdraw_non_fungibles"
+        )
+    })
+}
+
+// Prepend the fee lock from faucet, unless the manifest already locks the fee
+pub fn with_fee_lock(mut manifest: TransactionManifestV1) -> TransactionManifestV1 {
+    if !has_fee_lock(&manifest.instructions) {
+        let mut instructions = ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .build()
+            .instructions;
+        instructions.append(&mut manifest.instructions);
+        manifest.instructions = instructions;
+    }
+    manifest
+}